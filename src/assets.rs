@@ -1,5 +1,7 @@
 use crate::core::*;
 use crate::errors::*;
+use crate::streaming::*;
+use crate::GameState;
 use bevy::asset::io::*;
 use bevy::asset::saver::*;
 use bevy::asset::*;
@@ -7,28 +9,383 @@ use bevy::gltf::*;
 use bevy::pbr::experimental::meshlet::*;
 use bevy::prelude::*;
 use bevy::render::render_asset::RenderAssetUsages;
-use bevy::utils::HashMap;
+use bevy::render::texture::ImageLoaderSettings;
+use bevy::utils::{HashMap, HashSet};
 use bevy_rapier3d::prelude::*;
 use const_format::formatcp;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 pub const TERRAIN_PATH: &str = formatcp!("default.{}", TERRAIN_DETAILS_FILE_EXTENSION);
+/// Environment variable which can select the terrain (see [`TerrainSelection::from_args_or_env`]).
+pub const TERRAIN_ENV_VAR: &str = "TERRAIN_FILE";
 pub const TERRAIN_DETAILS_FILE_EXTENSION: &str = "terrain.bin";
 
+/// Identifies a processed terrain file.
+pub const TERRAIN_FILE_MAGIC: [u8; 4] = *b"MTTB";
+/// Increment this whenever the layout of [`ProcessedTerrainDetails`] changes (and add a migration to [`migrate_terrain_payload`] if possible).
+pub const TERRAIN_FORMAT_VERSION: u32 = 13;
+/// The Bevy version this crate is built against (the meshlet layout can change between Bevy releases).
+pub const BEVY_VERSION: &str = "0.14";
+
+//****************************************************************************
+// SETTINGS
+//****************************************************************************
+
+/// Loader settings for [`ProcessedTerrainDetailsAssetLoader`].
+/// Stored in the `.terrain.bin.meta` file, so each terrain can be tuned without recompiling.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct TerrainProcessSettings {
+    /// The shape used when generating colliders for each mesh primitive (unless overridden for a gLTF node).
+    pub collider_shape: TerrainColliderShape,
+    /// Whether to generate tangents for primitives that don't have them.
+    /// Note: meshlet generation requires tangents, so only disable this if the gLTF already provides them.
+    pub generate_tangents: bool,
+    /// Skips meshlet generation (e.g. when only the colliders are required).
+    pub skip_meshlets: bool,
+    /// Splits the terrain into a grid of square chunks of this size (on the XZ plane), so they can be spawned separately.
+    /// Meshes used by several gLTF nodes aren't split: each node is placed whole in the chunk containing its centre,
+    /// and shares its meshlets & colliders with the other nodes.
+    pub chunk_size: Option<f32>,
+    /// Generates TriMesh & convex decomposition colliders from a simplified version of each mesh.
+    pub collider_simplification: Option<ColliderSimplification>,
+}
+
+impl Default for TerrainProcessSettings {
+    fn default() -> Self {
+        Self {
+            collider_shape: TerrainColliderShape::TriMesh,
+            generate_tangents: true,
+            skip_meshlets: false,
+            chunk_size: None,
+            collider_simplification: None,
+        }
+    }
+}
+
+/// Saver settings for [`ProcessedTerrainSaver`].
+/// Stored in the `.terrain.bin.meta` file alongside [`TerrainProcessSettings`].
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct TerrainSaverSettings {
+    /// The ZSTD compression level used when saving the processed terrain.
+    pub compression_level: i32,
+}
+
+impl Default for TerrainSaverSettings {
+    fn default() -> Self {
+        Self {
+            compression_level: 19,
+        }
+    }
+}
+
 //****************************************************************************
 // ASSETS
 //****************************************************************************
 
 #[derive(Serialize, Deserialize)]
 pub struct SerialisedTerrainDetails {
+    #[serde(default)]
     pub gltf_path: String,
+    /// The gLTF scene to build the terrain from (uses the default scene if not specified).
+    #[serde(default)]
+    pub scene_name: Option<String>,
+    /// Additional gLTF files (e.g. tiles & props) merged into the terrain, each with its own placement.
+    #[serde(default)]
+    pub gltfs: Vec<SerialisedGltfSource>,
+    /// Replaces gLTF materials (by name) with a [`TerrainMaterial`].
+    #[serde(default)]
+    pub terrain_materials: HashMap<String, SerialisedTerrainMaterial>,
+    /// Overrides the collider shape of gLTF nodes (by name), taking priority over a `collider` in the node's extras.
+    #[serde(default)]
+    pub collider_overrides: HashMap<String, TerrainColliderShape>,
+    /// Physics materials which gLTF nodes can use (by name) with a `physics_material` extra.
+    #[serde(default)]
+    pub physics_materials: HashMap<String, TerrainPhysicsMaterial>,
+    /// Generates the terrain from a heightmap (can be used alongside, or instead of, the gLTF).
+    #[serde(default)]
+    pub heightmap: Option<SerialisedHeightmap>,
+}
+
+impl SerialisedTerrainDetails {
+    /// Returns every gLTF source, starting with `gltf_path` (if set).
+    pub fn gltf_sources(&self) -> Vec<SerialisedGltfSource> {
+        let mut gltf_sources = Vec::with_capacity(self.gltfs.len() + 1);
+
+        if !self.gltf_path.is_empty() {
+            gltf_sources.push(SerialisedGltfSource {
+                path: self.gltf_path.clone(),
+                scene_name: self.scene_name.clone(),
+                placement: SerialisedPlacement::default(),
+            });
+        }
+
+        gltf_sources.extend(self.gltfs.iter().cloned());
+        gltf_sources
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SerialisedGltfSource {
+    pub path: String,
+    /// The gLTF scene to use (uses the default scene if not specified).
+    #[serde(default)]
+    pub scene_name: Option<String>,
+    #[serde(default)]
+    pub placement: SerialisedPlacement,
+}
+
+/// Where a gLTF source is placed within the terrain.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SerialisedPlacement {
+    pub translation: Vec3,
+    /// Euler angles in degrees (applied in Y, X, Z order).
+    pub rotation: Vec3,
+    pub scale: Vec3,
+}
+
+impl Default for SerialisedPlacement {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::ZERO,
+            rotation: Vec3::ZERO,
+            scale: Vec3::ONE,
+        }
+    }
+}
+
+impl SerialisedPlacement {
+    pub fn to_transform(&self) -> Transform {
+        Transform {
+            translation: self.translation,
+            rotation: Quat::from_euler(
+                EulerRot::YXZ,
+                self.rotation.y.to_radians(),
+                self.rotation.x.to_radians(),
+                self.rotation.z.to_radians(),
+            ),
+            scale: self.scale,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SerialisedHeightmap {
+    /// Path to a greyscale PNG (8 or 16-bit), or a raw little-endian 16-bit heightmap (`.r16` / `.raw`).
+    pub path: String,
+    /// Size of a raw heightmap in pixels (not required for PNGs).
+    #[serde(default)]
+    pub raw_size: Option<UVec2>,
+    /// Size of the terrain along X & Z.
+    pub world_size: Vec2,
+    /// Height of the terrain at the maximum heightmap value.
+    pub height_scale: f32,
+    /// Number of vertices along X & Z of the generated mesh.
+    pub resolution: UVec2,
+    #[serde(default)]
+    pub material: SerialisedStandardMaterial,
+    /// Uses a [`TerrainMaterial`] instead of `material` (if set).
+    #[serde(default)]
+    pub terrain_material: Option<SerialisedTerrainMaterial>,
+    /// Generates the terrain material's splat map from the slope & height of the terrain (if it doesn't have one).
+    /// Requires `terrain_material`. Only heightmap terrain is supported, as the splat map is baked on its grid
+    /// (gLTF meshes using `terrain_materials` need a `splat_map`).
+    #[serde(default)]
+    pub texturing_rules: Vec<TerrainLayerRule>,
 }
 
 #[derive(Asset, TypePath, Serialize, Deserialize)]
 pub struct ProcessedTerrainDetails {
-    pub gltf_path: String,
+    /// The gLTF files referenced by [`SerialisedMaterial::Gltf`].
+    pub gltf_paths: Vec<String>,
+    /// Materials referenced by each [`SerialisedMeshlet::material_index`].
+    pub materials: Vec<SerialisedMaterial>,
+    /// Unique meshlet meshes referenced by each [`SerialisedMeshlet::mesh_index`].
+    pub meshlet_meshes: Vec<SerialisedMeshletMesh>,
     pub meshlet_nodes: Vec<SerialisedMeshletNode>,
-    pub colliders: Vec<TerrainColliderNode>,
+    /// Unique collider shapes referenced by each [`SerialisedTerrainCollider::shape_index`].
+    pub collider_shapes: Vec<Collider>,
+    pub colliders: Vec<SerialisedTerrainColliderNode>,
+    /// The size of each chunk (see [`TerrainProcessSettings::chunk_size`]).
+    pub chunk_size: Option<f32>,
+    /// Spatial chunks of the terrain (only used if [`TerrainProcessSettings::chunk_size`] is set).
+    pub chunks: Vec<SerialisedTerrainChunk>,
+}
+
+impl ProcessedTerrainDetails {
+    /// Checks every meshlet (including those of chunks) refers to an existing meshlet mesh & material.
+    pub fn validate_meshlet_indices(&self) -> Result<(), LoaderError> {
+        fn validate_node(
+            node: &SerialisedMeshletNode,
+            mesh_count: usize,
+            material_count: usize,
+        ) -> Result<(), LoaderError> {
+            for mesh in &node.meshes {
+                if mesh.mesh_index >= mesh_count || mesh.material_index >= material_count {
+                    return Err(LoaderError::InvalidMeshletIndex {
+                        node: node.path.clone(),
+                        mesh_index: mesh.mesh_index,
+                        material_index: mesh.material_index,
+                        mesh_count,
+                        material_count,
+                    });
+                }
+            }
+
+            node.children
+                .iter()
+                .try_for_each(|child| validate_node(child, mesh_count, material_count))
+        }
+
+        self.meshlet_nodes
+            .iter()
+            .chain(self.chunks.iter().map(|chunk| &chunk.meshlet_node))
+            .try_for_each(|node| {
+                validate_node(node, self.meshlet_meshes.len(), self.materials.len())
+            })
+    }
+
+    /// Checks every collider (including those of chunks) refers to an existing collider shape.
+    pub fn validate_collider_shape_indices(&self) -> Result<(), LoaderError> {
+        fn validate_node(
+            node: &SerialisedTerrainColliderNode,
+            shape_count: usize,
+        ) -> Result<(), LoaderError> {
+            if let Some(collider) = node
+                .colliders
+                .iter()
+                .find(|collider| collider.shape_index >= shape_count)
+            {
+                return Err(LoaderError::InvalidColliderShapeIndex {
+                    shape_index: collider.shape_index,
+                    shape_count,
+                });
+            }
+
+            node.children
+                .iter()
+                .try_for_each(|child| validate_node(child, shape_count))
+        }
+
+        self.colliders
+            .iter()
+            .chain(self.chunks.iter().map(|chunk| &chunk.collider_node))
+            .try_for_each(|node| validate_node(node, self.collider_shapes.len()))
+    }
+}
+
+//****************************************************************************
+// FILE FORMAT
+//****************************************************************************
+
+/// Uncompressed header written after [`TERRAIN_FILE_MAGIC`] at the start of each processed terrain file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TerrainFileHeader {
+    pub format_version: u32,
+    pub crate_version: String,
+    pub bevy_version: String,
+    pub meshlet_mesh_version: u64,
+}
+
+impl TerrainFileHeader {
+    /// The header for files written by this build.
+    pub fn current() -> Self {
+        Self {
+            format_version: TERRAIN_FORMAT_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            bevy_version: BEVY_VERSION.to_string(),
+            meshlet_mesh_version: MESHLET_MESH_ASSET_VERSION,
+        }
+    }
+}
+
+/// Writes the magic number, header and ZSTD-compressed terrain details.
+pub fn encode_processed_terrain(
+    terrain: &ProcessedTerrainDetails,
+    compression_level: i32,
+) -> Result<Vec<u8>, SaverError> {
+    let mut bytes = TERRAIN_FILE_MAGIC.to_vec();
+    bytes = postcard::to_extend(&TerrainFileHeader::current(), bytes)?;
+
+    let payload = postcard::to_allocvec(terrain)?;
+    bytes.extend(zstd::encode_all(payload.as_slice(), compression_level)?);
+    Ok(bytes)
+}
+
+/// Checks the header of a processed terrain file, then decompresses and decodes (or migrates) the terrain details.
+pub fn decode_processed_terrain(
+    bytes: &[u8],
+) -> Result<(TerrainFileHeader, ProcessedTerrainDetails), LoaderError> {
+    let (header, payload) = decompress_processed_terrain(bytes)?;
+    let terrain = migrate_terrain_payload(header.format_version, &payload)?;
+    Ok((header, terrain))
+}
+
+/// Checks the header of a processed terrain file, and returns it with the decompressed (but still encoded) payload.
+pub fn decompress_processed_terrain(
+    bytes: &[u8],
+) -> Result<(TerrainFileHeader, Vec<u8>), LoaderError> {
+    // Files from before the header was added (or other files entirely) don't start with the magic number
+    let Some(bytes) = bytes.strip_prefix(&TERRAIN_FILE_MAGIC) else {
+        return Err(LoaderError::IncompatibleTerrainFile(
+            "missing header".to_string(),
+        ));
+    };
+
+    let (header, compressed_bytes): (TerrainFileHeader, _) = postcard::take_from_bytes(bytes)
+        .map_err(|error| {
+            LoaderError::IncompatibleTerrainFile(format!("unreadable header [{}]", error))
+        })?;
+    let current = TerrainFileHeader::current();
+
+    if header.format_version > current.format_version {
+        return Err(LoaderError::IncompatibleTerrainFile(format!(
+            "format version {0} is newer than the supported version {1}",
+            header.format_version, current.format_version
+        )));
+    }
+
+    if header.bevy_version != current.bevy_version
+        || header.meshlet_mesh_version != current.meshlet_mesh_version
+    {
+        return Err(LoaderError::IncompatibleTerrainFile(format!(
+            "written with Bevy {0} (meshlet version {1}), expected Bevy {2} (meshlet version {3})",
+            header.bevy_version,
+            header.meshlet_mesh_version,
+            current.bevy_version,
+            current.meshlet_mesh_version
+        )));
+    }
+
+    if header.crate_version != current.crate_version {
+        debug!(
+            "Terrain file was written by version {0} of this crate",
+            header.crate_version
+        );
+    }
+
+    let payload = zstd::stream::decode_all(compressed_bytes)?;
+    Ok((header, payload))
+}
+
+/// Migration hook for older format versions.
+/// When bumping [`TERRAIN_FORMAT_VERSION`], add an arm here that decodes the old layout and converts it.
+pub(crate) fn migrate_terrain_payload(
+    format_version: u32,
+    payload: &[u8],
+) -> Result<ProcessedTerrainDetails, LoaderError> {
+    match format_version {
+        TERRAIN_FORMAT_VERSION => Ok(postcard::from_bytes(payload)?),
+        _ => Err(LoaderError::IncompatibleTerrainFile(format!(
+            "no migration from format version {0} to {1}",
+            format_version, TERRAIN_FORMAT_VERSION
+        ))),
+    }
 }
 
 //****************************************************************************
@@ -36,45 +393,132 @@ pub struct ProcessedTerrainDetails {
 //****************************************************************************
 
 #[derive(Default)]
-pub struct ProcessedTerrainDetailsAssetLoader;
+pub struct ProcessedTerrainDetailsAssetLoader {
+    /// Where the duration of each processing step is recorded.
+    pub timings: TerrainProcessTimings,
+}
 
 impl AssetLoader for ProcessedTerrainDetailsAssetLoader {
     type Asset = ProcessedTerrainDetails;
-    type Settings = ();
+    type Settings = TerrainProcessSettings;
     type Error = LoaderError;
 
     async fn load<'a>(
         &'a self,
         reader: &'a mut Reader<'_>,
-        _settings: &'a Self::Settings,
+        settings: &'a Self::Settings,
         load_context: &'a mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
         let ron: SerialisedTerrainDetails = ron::de::from_bytes(&bytes)?;
 
+        let gltf_sources = ron.gltf_sources();
+
+        if gltf_sources.is_empty() && ron.heightmap.is_none() {
+            return Err(StartupError::MissingGltfPath.into());
+        }
+
+        let heightmap_terrain_material = ron
+            .heightmap
+            .as_ref()
+            .and_then(|heightmap| heightmap.terrain_material.as_ref());
+
+        for terrain_material in ron
+            .terrain_materials
+            .values()
+            .chain(heightmap_terrain_material)
+        {
+            if terrain_material.layers.len() > TERRAIN_MATERIAL_LAYERS {
+                return Err(LoaderError::TooManyTerrainLayers(
+                    terrain_material.layers.len(),
+                ));
+            }
+        }
+
+        for rule in ron
+            .heightmap
+            .iter()
+            .flat_map(|heightmap| &heightmap.texturing_rules)
+        {
+            if rule.layer >= TERRAIN_MATERIAL_LAYERS {
+                return Err(LoaderError::InvalidTerrainLayer(rule.layer));
+            }
+        }
+
+        // Rules are baked against the heightmap's grid, so only apply to its terrain material
+        if let Some(heightmap) = &ron.heightmap {
+            if !heightmap.texturing_rules.is_empty() && heightmap.terrain_material.is_none() {
+                return Err(LoaderError::TexturingRulesWithoutTerrainMaterial);
+            }
+        }
+
         // Generate the meshlets & colliders
+        let load_start = Instant::now();
+        self.timings.clear(load_context.path());
+        let mut materials = Vec::new();
+        let mut meshlet_meshes = Vec::new();
         let mut meshlet_nodes = Vec::new();
+        let mut collider_shapes = Vec::new();
         let mut colliders = Vec::new();
 
-        if !ron.gltf_path.is_empty() {
+        // When chunking, meshes are transformed into world-space and split up after everything has been loaded
+        let mut world_meshes: Vec<WorldMesh> = Vec::new();
+        // ...apart from the nodes using a shared mesh, which are added to the chunk containing them
+        let mut chunk_instances: Vec<ChunkInstance> = Vec::new();
+        // Names of the terrain materials which replace a gLTF material (the rest are probably typos)
+        let mut used_terrain_materials: HashSet<&str> = HashSet::new();
+
+        for (gltf_index, gltf_source) in gltf_sources.iter().enumerate() {
+            let step_start = Instant::now();
+            let placement = gltf_source.placement.to_transform();
+
+            // Material indices of this gLTF start after those of the previous sources
+            let material_offset = materials.len();
+
+            // The gLTF source is required to find the root nodes of each scene
             let model_asset = load_context
                 .loader()
+                .with_settings(|settings: &mut GltfLoaderSettings| settings.include_source = true)
                 .direct()
-                .load::<Gltf>(&ron.gltf_path)
+                .load::<Gltf>(&gltf_source.path)
                 .await?;
             let gltf: &Gltf = model_asset.get();
 
-            debug!("Terrain gLTF directly loaded");
+            debug!(
+                "Terrain gLTF '{0}' directly loaded in {1:?}",
+                gltf_source.path,
+                step_start.elapsed()
+            );
+            self.timings.record(
+                load_context.path(),
+                format!("Loaded gLTF '{}'", gltf_source.path),
+                step_start.elapsed(),
+            );
+            let step_start = Instant::now();
+
+            // Name of the first node using each Mesh (to help identify meshes which fail to process)
+            let mut mesh_node_names: HashMap<Handle<GltfMesh>, String> = HashMap::new();
+
+            for gltf_node in (0..gltf.nodes.len())
+                .filter_map(|node_index| model_asset.get_labeled(format!("Node{}", node_index)))
+                .filter_map(|gltf_node_asset| gltf_node_asset.get::<GltfNode>())
+            {
+                if let Some(gltf_mesh_handle) = &gltf_node.mesh {
+                    mesh_node_names
+                        .entry(gltf_mesh_handle.clone())
+                        .or_insert_with(|| gltf_node.name.clone());
+                }
+            }
 
-            // Generate the meshlets and colliders for each Mesh in this GLTF file
-            let mut processed_meshlets: HashMap<
+            // Get the primitive meshes & material indices for each Mesh in this GLTF file
+            let mut gltf_meshes: HashMap<
                 Handle<GltfMesh>,
-                Vec<Option<(MeshletMesh, usize)>>,
+                Vec<(Mesh, Option<usize>, TerrainMeshSource)>,
             > = HashMap::with_capacity(gltf.meshes.len());
 
-            let mut processed_colliders: HashMap<Handle<GltfMesh>, Vec<Collider>> =
-                HashMap::with_capacity(gltf.meshes.len());
+            // Processing overrides of each Mesh, used by nodes which don't override them
+            let mut mesh_extras: HashMap<Handle<GltfMesh>, TerrainGltfExtras> = HashMap::new();
 
             for mesh_index in 0..gltf.meshes.len() {
                 // Unwraps should be safe
@@ -86,164 +530,467 @@ impl AssetLoader for ProcessedTerrainDetailsAssetLoader {
 
                 // Get the handle as well (necessary for lookups below)
                 let gltf_mesh_handle: Handle<GltfMesh> =
-                    load_context.load(format!("{0}#Mesh{1}", &ron.gltf_path, mesh_index));
+                    load_context.load(format!("{0}#Mesh{1}", &gltf_source.path, mesh_index));
 
-                let mut meshlets: Vec<Option<(MeshletMesh, usize)>> =
+                let mut primitives: Vec<(Mesh, Option<usize>, TerrainMeshSource)> =
                     Vec::with_capacity(gltf_mesh.primitives.len());
-                let mut colliders: Vec<Collider> = Vec::with_capacity(gltf_mesh.primitives.len());
 
                 for (primitive_index, primitive) in gltf_mesh.primitives.iter().enumerate() {
                     let mesh_asset = model_asset
                         .get_labeled(format!("Mesh{0}/Primitive{1}", mesh_index, primitive_index))
                         .unwrap();
 
-                    let mut mesh: Mesh = mesh_asset.get::<Mesh>().unwrap().clone();
-
-                    debug!("Generating collider...");
-
-                    let Some(collider) =
-                        Collider::from_bevy_mesh(&mesh, &ComputedColliderShape::TriMesh)
-                    else {
-                        return Err(LoaderError::Other(
-                            "Unable to generate collider for terrain mesh".to_string(),
-                        ));
+                    let mesh: Mesh = mesh_asset.get::<Mesh>().unwrap().clone();
+
+                    let material_index = primitive.material.as_ref().map(|material_handle| {
+                        material_offset
+                            + gltf
+                                .materials
+                                .iter()
+                                .position(|m| m == material_handle)
+                                .unwrap()
+                    });
+
+                    let mesh_source = TerrainMeshSource {
+                        gltf_path: Some(gltf_source.path.clone()),
+                        mesh_index: Some(mesh_index),
+                        primitive_index,
+                        node_name: mesh_node_names.get(&gltf_mesh_handle).cloned(),
+                        chunk: None,
                     };
 
-                    debug!("Collider generated");
-                    colliders.push(collider);
-
-                    if let Some(material_handle) = &primitive.material {
-                        if !mesh.contains_attribute(Mesh::ATTRIBUTE_TANGENT) {
-                            debug!("Generating tangents...");
+                    primitives.push((mesh, material_index, mesh_source));
+                }
 
-                            mesh.generate_tangents().map_err(|e| {
-                                LoaderError::Other(format!(
-                                    "Unable to generate tangent for terrain mesh [{0}]",
-                                    e
-                                ))
-                            })?;
+                mesh_extras.insert(
+                    gltf_mesh_handle.clone(),
+                    TerrainGltfExtras::from_gltf_extras(gltf_mesh.extras.as_ref()).map_err(
+                        |error| LoaderError::InvalidGltfMeshExtras {
+                            mesh: gltf_mesh.name.clone(),
+                            error,
+                        },
+                    )?,
+                );
+
+                gltf_meshes.insert(gltf_mesh_handle, primitives);
+            }
 
-                            debug!("Tangents generated");
-                        }
+            // Only convert the root nodes, as children are converted recursively below
+            let root_nodes: Vec<&GltfNode> =
+                gltf_root_node_indices(&model_asset, gltf_source.scene_name.as_deref())?
+                    .into_iter()
+                    .map(|node_index| {
+                        // Unwraps should be safe (otherwise there's a bug in the GltfLoader)
+                        model_asset
+                            .get_labeled(format!("Node{}", node_index))
+                            .unwrap()
+                            .get::<GltfNode>()
+                            .unwrap()
+                    })
+                    .collect();
 
-                        debug!("Generating meshlets...");
+            let node_options =
+                GltfNodeOptions::from_root_nodes(&root_nodes, &mesh_extras, &ron, settings)?;
 
-                        let meshlet = MeshletMesh::from_mesh(&mesh).map_err(|e| {
-                            LoaderError::Other(format!(
-                                "Unable to generate meshlet for terrain mesh [{0}]",
-                                e
-                            ))
-                        })?;
+            // Paths of the first gLTF's nodes aren't prefixed, so they stay the same as with a single gLTF
+            let root_path = (gltf_index > 0).then(|| format!("Gltf{}", gltf_index));
 
-                        let material_index = gltf
-                            .materials
-                            .iter()
-                            .position(|m| m == material_handle)
-                            .unwrap();
+            // Each primitive's meshlet mesh & collider (for each shape), so nodes sharing a gLTF mesh share them
+            let mut primitive_cache = GltfPrimitiveCache::new(gltf_index, &gltf_meshes);
 
-                        debug!("Meshlets generated");
+            if settings.chunk_size.is_some() {
+                let mut instances = Vec::new();
 
-                        meshlets.push(Some((meshlet, material_index)));
-                    } else {
-                        meshlets.push(None);
-                    }
+                for gltf_node in root_nodes {
+                    gltf_node_to_world_meshes(
+                        gltf_node,
+                        GlobalTransform::from(placement),
+                        root_path.as_deref(),
+                        &gltf_meshes,
+                        &node_options,
+                        &mut world_meshes,
+                        &mut instances,
+                    );
                 }
 
-                processed_meshlets.insert(gltf_mesh_handle.clone(), meshlets);
-                processed_colliders.insert(gltf_mesh_handle, colliders);
+                chunk_instances.extend(build_chunk_instances(
+                    instances,
+                    &mut primitive_cache,
+                    &mut meshlet_meshes,
+                    &mut collider_shapes,
+                    settings,
+                )?);
+            } else {
+                for gltf_node in root_nodes {
+                    let mut serialised_meshlet_node = gltf_node_to_meshlet_node(
+                        gltf_node,
+                        &node_options,
+                        &mut primitive_cache,
+                        &mut meshlet_meshes,
+                        root_path.as_deref(),
+                        settings,
+                    )?;
+                    serialised_meshlet_node.transform =
+                        placement * serialised_meshlet_node.transform;
+
+                    meshlet_nodes.push(serialised_meshlet_node);
+
+                    let mut mesh_collider = gltf_node_to_collider_node(
+                        gltf_node,
+                        &node_options,
+                        &mut primitive_cache,
+                        &mut collider_shapes,
+                        settings,
+                    )?;
+                    mesh_collider.transform = placement * mesh_collider.transform;
+
+                    colliders.push(mesh_collider);
+                }
             }
 
-            for node_index in 0..gltf.nodes.len() {
-                // Unwraps should be safe (otherwise there's a bug in the GltfLoader)
-                let gltf_node_asset = model_asset
-                    .get_labeled(format!("Node{}", node_index))
-                    .unwrap();
-
-                let gltf_node = gltf_node_asset.get::<GltfNode>().unwrap();
-
-                let serialised_meshlet_node =
-                    gltf_node_to_meshlet_node(gltf_node, &processed_meshlets);
-
-                meshlet_nodes.push(serialised_meshlet_node);
+            used_terrain_materials.extend(
+                gltf.named_materials
+                    .keys()
+                    .filter_map(|name| ron.terrain_materials.get_key_value(&**name))
+                    .map(|(name, _)| name.as_str()),
+            );
+
+            materials.extend(gltf.materials.iter().enumerate().map(
+                |(material, material_handle)| {
+                    let terrain_material = gltf
+                        .named_materials
+                        .iter()
+                        .find(|(_, named_handle)| *named_handle == material_handle)
+                        .and_then(|(name, _)| ron.terrain_materials.get(&**name));
 
-                let mesh_collider = gltf_node_to_collider_node(gltf_node, &processed_colliders);
+                    match terrain_material {
+                        Some(terrain_material) => {
+                            SerialisedMaterial::Terrain(terrain_material.clone())
+                        }
+                        None => SerialisedMaterial::Gltf {
+                            source: gltf_index,
+                            material,
+                        },
+                    }
+                },
+            ));
 
-                colliders.push(mesh_collider);
-            }
+            debug!(
+                "Terrain gLTF meshes processed in {:?}",
+                step_start.elapsed()
+            );
+            self.timings.record(
+                load_context.path(),
+                format!("Processed gLTF '{}' meshes", gltf_source.path),
+                step_start.elapsed(),
+            );
 
             fn gltf_node_to_meshlet_node(
                 gltf_node: &GltfNode,
-                processed_meshlets: &HashMap<Handle<GltfMesh>, Vec<Option<(MeshletMesh, usize)>>>,
-            ) -> SerialisedMeshletNode {
+                node_options: &GltfNodeOptions,
+                primitive_cache: &mut GltfPrimitiveCache,
+                meshlet_meshes: &mut Vec<SerialisedMeshletMesh>,
+                parent_path: Option<&str>,
+                settings: &TerrainProcessSettings,
+            ) -> Result<SerialisedMeshletNode, LoaderError> {
+                let path = meshlet_node_path(parent_path, gltf_node.index);
+
                 let children = gltf_node
                     .children
                     .iter()
                     .map(|child_gltf_node| {
-                        gltf_node_to_meshlet_node(child_gltf_node, processed_meshlets)
+                        gltf_node_to_meshlet_node(
+                            child_gltf_node,
+                            node_options,
+                            primitive_cache,
+                            meshlet_meshes,
+                            Some(&path),
+                            settings,
+                        )
                     })
-                    .collect();
-
-                let meshes = if let Some(gltf_mesh_handle) = &gltf_node.mesh {
-                    let meshlets = processed_meshlets.get(gltf_mesh_handle).unwrap();
-
-                    meshlets
-                        .iter()
-                        .filter_map(|inner_meshlet| {
-                            inner_meshlet
-                                .clone()
-                                .map(|inner_meshlet| SerialisedMeshlet {
-                                    mesh: inner_meshlet.0,
-                                    material_index: inner_meshlet.1,
-                                })
-                        })
-                        .collect()
-                } else {
-                    Vec::new()
+                    .collect::<Result<_, _>>()?;
+
+                let meshes = match gltf_node
+                    .mesh
+                    .as_ref()
+                    .filter(|_| node_options.get(gltf_node).meshlets)
+                {
+                    Some(gltf_mesh_handle) => {
+                        primitive_cache.meshlets(gltf_mesh_handle, meshlet_meshes, settings)?
+                    }
+                    None => Vec::new(),
                 };
 
-                SerialisedMeshletNode {
+                Ok(SerialisedMeshletNode {
+                    path,
                     meshes,
                     transform: gltf_node.transform,
                     children,
+                })
+            }
+
+            fn gltf_node_to_world_meshes(
+                gltf_node: &GltfNode,
+                parent_transform: GlobalTransform,
+                parent_path: Option<&str>,
+                gltf_meshes: &HashMap<
+                    Handle<GltfMesh>,
+                    Vec<(Mesh, Option<usize>, TerrainMeshSource)>,
+                >,
+                node_options: &GltfNodeOptions,
+                world_meshes: &mut Vec<WorldMesh>,
+                instances: &mut Vec<WorldMeshInstance>,
+            ) {
+                let path = meshlet_node_path(parent_path, gltf_node.index);
+                let global_transform = parent_transform.mul_transform(gltf_node.transform);
+                let options = node_options.get(gltf_node);
+
+                if let Some(gltf_mesh_handle) = gltf_node
+                    .mesh
+                    .as_ref()
+                    .filter(|gltf_mesh_handle| node_options.is_instanced(gltf_mesh_handle))
+                {
+                    instances.push(WorldMeshInstance {
+                        path: path.clone(),
+                        gltf_mesh: gltf_mesh_handle.clone(),
+                        transform: global_transform.compute_transform(),
+                        meshlets: options.meshlets,
+                        collider_shape: options.collider_shape,
+                        physics_material: options.physics_material,
+                        collision_group: options.collision_group,
+                        streaming_chunk: options.streaming_chunk,
+                    });
+                } else if let Some(gltf_mesh_handle) = &gltf_node.mesh {
+                    for (mesh, material_index, mesh_source) in
+                        gltf_meshes.get(gltf_mesh_handle).unwrap()
+                    {
+                        world_meshes.push(WorldMesh {
+                            mesh: mesh
+                                .clone()
+                                .transformed_by(global_transform.compute_transform()),
+                            material_index: material_index.filter(|_| options.meshlets),
+                            collider_shape: options.collider_shape,
+                            physics_material: options.physics_material,
+                            collision_group: options.collision_group,
+                            streaming_chunk: options.streaming_chunk,
+                            source: TerrainMeshSource {
+                                node_name: Some(gltf_node.name.clone()),
+                                ..mesh_source.clone()
+                            },
+                        });
+                    }
+                }
+
+                for child_gltf_node in &gltf_node.children {
+                    gltf_node_to_world_meshes(
+                        child_gltf_node,
+                        global_transform,
+                        Some(&path),
+                        gltf_meshes,
+                        node_options,
+                        world_meshes,
+                        instances,
+                    );
                 }
             }
 
             fn gltf_node_to_collider_node(
                 gltf_node: &GltfNode,
-                processed_colliders: &HashMap<Handle<GltfMesh>, Vec<Collider>>,
-            ) -> TerrainColliderNode {
+                node_options: &GltfNodeOptions,
+                primitive_cache: &mut GltfPrimitiveCache,
+                collider_shapes: &mut Vec<Collider>,
+                settings: &TerrainProcessSettings,
+            ) -> Result<SerialisedTerrainColliderNode, LoaderError> {
                 let children = gltf_node
                     .children
                     .iter()
                     .map(|child_gltf_node| {
-                        gltf_node_to_collider_node(child_gltf_node, processed_colliders)
+                        gltf_node_to_collider_node(
+                            child_gltf_node,
+                            node_options,
+                            primitive_cache,
+                            collider_shapes,
+                            settings,
+                        )
                     })
-                    .collect();
-
-                let colliders = if let Some(gltf_mesh_handle) = &gltf_node.mesh {
-                    let colliders = processed_colliders.get(gltf_mesh_handle).unwrap();
-
-                    colliders.clone()
-                } else {
-                    Vec::new()
+                    .collect::<Result<_, _>>()?;
+
+                let options = node_options.get(gltf_node);
+                let collider_shape = options.collider_shape;
+
+                let colliders = match &gltf_node.mesh {
+                    Some(gltf_mesh_handle) => primitive_cache.colliders(
+                        gltf_mesh_handle,
+                        collider_shape,
+                        collider_shapes,
+                        settings,
+                    )?,
+                    None => Vec::new(),
                 };
 
-                TerrainColliderNode {
+                Ok(SerialisedTerrainColliderNode {
                     colliders,
+                    collider_shape,
+                    physics_material: options.physics_material,
+                    collision_group: options.collision_group,
                     transform: gltf_node.transform,
                     children,
+                })
+            }
+        }
+
+        for name in ron.terrain_materials.keys() {
+            if !used_terrain_materials.contains(name.as_str()) {
+                warn!(
+                    "Terrain material '{}' isn't used, as no gLTF material has that name",
+                    name
+                );
+            }
+        }
+
+        if let Some(heightmap_source) = &ron.heightmap {
+            let step_start = Instant::now();
+            let heightmap = load_heightmap(heightmap_source, load_context).await?;
+
+            debug!("Generating heightmap mesh...");
+
+            let mesh = heightmap.to_mesh(
+                heightmap_source.world_size,
+                heightmap_source.height_scale,
+                heightmap_source.resolution,
+            );
+
+            let material_index = materials.len();
+            materials.push(match &heightmap_source.terrain_material {
+                Some(terrain_material) => {
+                    let mut terrain_material = terrain_material.clone();
+
+                    if terrain_material.splat_map.is_none()
+                        && !heightmap_source.texturing_rules.is_empty()
+                    {
+                        terrain_material.baked_splat_map = BakedSplatMap::from_grid_mesh(
+                            &mesh,
+                            heightmap_source.resolution.max(UVec2::splat(2)),
+                            &heightmap_source.texturing_rules,
+                        );
+                    }
+
+                    SerialisedMaterial::Terrain(terrain_material)
                 }
+                None => SerialisedMaterial::Standard(heightmap_source.material.clone()),
+            });
+
+            if settings.chunk_size.is_some() {
+                world_meshes.push(WorldMesh {
+                    mesh,
+                    material_index: Some(material_index),
+                    collider_shape: settings.collider_shape,
+                    physics_material: None,
+                    collision_group: None,
+                    streaming_chunk: None,
+                    source: TerrainMeshSource::default(),
+                });
+            } else {
+                let (collider, meshlet) = process_mesh(
+                    mesh,
+                    true,
+                    settings.collider_shape,
+                    &TerrainMeshSource::default(),
+                    settings,
+                )?;
+
+                meshlet_nodes.push(SerialisedMeshletNode {
+                    path: "Heightmap".to_string(),
+                    meshes: meshlet
+                        .map(|mesh| {
+                            meshlet_meshes.push(SerialisedMeshletMesh {
+                                label: meshlet_mesh_label("Heightmap"),
+                                mesh,
+                            });
+
+                            SerialisedMeshlet {
+                                mesh_index: meshlet_meshes.len() - 1,
+                                material_index,
+                                primitive_index: 0,
+                            }
+                        })
+                        .into_iter()
+                        .collect(),
+                    transform: Transform::IDENTITY,
+                    children: Vec::new(),
+                });
+
+                colliders.push(SerialisedTerrainColliderNode {
+                    colliders: collider
+                        .map(|collider| collider.into_serialised(&mut collider_shapes))
+                        .into_iter()
+                        .collect(),
+                    collider_shape: settings.collider_shape,
+                    physics_material: None,
+                    collision_group: None,
+                    transform: Transform::IDENTITY,
+                    children: Vec::new(),
+                });
             }
-        } else {
-            return Err(LoaderError::Other(
-                StartupError::MissingGltfPath.to_string(),
-            ));
+
+            debug!("Terrain heightmap processed in {:?}", step_start.elapsed());
+            self.timings.record(
+                load_context.path(),
+                "Processed heightmap".to_string(),
+                step_start.elapsed(),
+            );
+        }
+
+        let chunks = match settings.chunk_size {
+            Some(chunk_size) => {
+                let step_start = Instant::now();
+                let chunks = build_chunks(
+                    world_meshes,
+                    chunk_instances,
+                    chunk_size,
+                    &mut meshlet_meshes,
+                    &mut collider_shapes,
+                    settings,
+                )?;
+
+                debug!(
+                    "Terrain split into {0} chunks in {1:?}",
+                    chunks.len(),
+                    step_start.elapsed()
+                );
+                self.timings.record(
+                    load_context.path(),
+                    format!("Split into {} chunks", chunks.len()),
+                    step_start.elapsed(),
+                );
+                chunks
+            }
+            None => Vec::new(),
         };
 
+        debug!(
+            "Terrain {0:?} processed in {1:?}",
+            load_context.path(),
+            load_start.elapsed()
+        );
+        self.timings.record(
+            load_context.path(),
+            "Processed terrain".to_string(),
+            load_start.elapsed(),
+        );
+
         Ok(ProcessedTerrainDetails {
-            gltf_path: ron.gltf_path,
+            gltf_paths: gltf_sources
+                .into_iter()
+                .map(|gltf_source| gltf_source.path)
+                .collect(),
+            materials,
+            meshlet_meshes,
             meshlet_nodes,
+            collider_shapes,
             colliders,
+            chunk_size: settings.chunk_size,
+            chunks,
         })
     }
 
@@ -252,6 +999,696 @@ impl AssetLoader for ProcessedTerrainDetailsAssetLoader {
     }
 }
 
+/// Generates the collider for a mesh (unless the shape is [`TerrainColliderShape::None`]), plus the meshlets if it has a material (and meshlets aren't skipped).
+/// `mesh_source` identifies the mesh in any errors.
+fn process_mesh(
+    mut mesh: Mesh,
+    has_material: bool,
+    collider_shape: TerrainColliderShape,
+    mesh_source: &TerrainMeshSource,
+    settings: &TerrainProcessSettings,
+) -> Result<(Option<TerrainCollider>, Option<MeshletMesh>), LoaderError> {
+    let collider = generate_collider(&mesh, collider_shape, mesh_source, settings)?;
+
+    if settings.skip_meshlets || !has_material {
+        return Ok((collider, None));
+    }
+
+    if settings.generate_tangents && !mesh.contains_attribute(Mesh::ATTRIBUTE_TANGENT) {
+        debug!("Generating tangents...");
+
+        mesh.generate_tangents()
+            .map_err(|error| LoaderError::TangentGeneration {
+                mesh: mesh_source.clone(),
+                error,
+            })?;
+
+        debug!("Tangents generated");
+    }
+
+    debug!("Generating meshlets...");
+
+    let meshlet =
+        MeshletMesh::from_mesh(&mesh).map_err(|error| LoaderError::MeshletGeneration {
+            mesh: mesh_source.clone(),
+            error,
+        })?;
+
+    debug!("Meshlets generated");
+
+    Ok((collider, Some(meshlet)))
+}
+
+fn generate_collider(
+    mesh: &Mesh,
+    collider_shape: TerrainColliderShape,
+    mesh_source: &TerrainMeshSource,
+    settings: &TerrainProcessSettings,
+) -> Result<Option<TerrainCollider>, LoaderError> {
+    if collider_shape == TerrainColliderShape::None {
+        return Ok(None);
+    }
+
+    debug!("Generating {:?} collider...", collider_shape);
+
+    let heightfield = match collider_shape {
+        TerrainColliderShape::Heightfield => HeightfieldGrid::from_mesh(mesh),
+        _ => None,
+    };
+
+    let collider = if let Some(heightfield) = heightfield {
+        debug!(
+            "Using {0}x{1} heightfield collider",
+            heightfield.num_cols, heightfield.num_rows
+        );
+        Some(heightfield.to_collider())
+    } else if let Some(computed_shape) = collider_shape.to_computed_shape() {
+        // Convex hulls only use the vertices, so simplifying the triangles wouldn't change them
+        // (a heightfield which isn't a regular grid falls back to a TriMesh)
+        let simplified_mesh = match collider_shape {
+            TerrainColliderShape::TriMesh
+            | TerrainColliderShape::Heightfield
+            | TerrainColliderShape::ConvexDecomposition => settings
+                .collider_simplification
+                .as_ref()
+                .and_then(|simplification| simplify_collision_mesh(mesh, simplification)),
+            _ => None,
+        };
+
+        if let Some(simplified_mesh) = &simplified_mesh {
+            debug!(
+                "Collider mesh simplified from {0} to {1} indices",
+                mesh.indices().map_or(0, |indices| indices.len()),
+                simplified_mesh.indices().map_or(0, |indices| indices.len())
+            );
+        }
+
+        Collider::from_bevy_mesh(simplified_mesh.as_ref().unwrap_or(mesh), &computed_shape)
+            .map(TerrainCollider::from)
+    } else {
+        TerrainCollider::fit_primitive(mesh, collider_shape)
+    };
+
+    let Some(collider) = collider else {
+        return Err(LoaderError::ColliderGeneration(mesh_source.clone()));
+    };
+
+    debug!("Collider generated");
+
+    Ok(Some(collider))
+}
+
+/// A mesh primitive transformed into world-space, before it's split into chunks.
+struct WorldMesh {
+    mesh: Mesh,
+    material_index: Option<usize>,
+    /// [`TerrainColliderShape::None`] if the mesh doesn't need a collider (e.g. when a collision node replaces it).
+    collider_shape: TerrainColliderShape,
+    physics_material: Option<TerrainPhysicsMaterial>,
+    collision_group: Option<u32>,
+    /// Places the whole mesh in this chunk, instead of splitting it.
+    streaming_chunk: Option<IVec2>,
+    source: TerrainMeshSource,
+}
+
+/// A node using a gLTF mesh shared with other nodes, which is kept whole (instead of being split into chunks).
+struct WorldMeshInstance {
+    /// See [`SerialisedMeshletNode::path`].
+    path: String,
+    gltf_mesh: Handle<GltfMesh>,
+    /// World-space transform of the node.
+    transform: Transform,
+    meshlets: bool,
+    collider_shape: TerrainColliderShape,
+    physics_material: Option<TerrainPhysicsMaterial>,
+    collision_group: Option<u32>,
+    streaming_chunk: Option<IVec2>,
+}
+
+/// The nodes of a [`WorldMeshInstance`], added to the chunk containing it.
+struct ChunkInstance {
+    /// World-space bounds of the instance.
+    min: Vec3,
+    max: Vec3,
+    streaming_chunk: Option<IVec2>,
+    meshlet_node: SerialisedMeshletNode,
+    collider_node: SerialisedTerrainColliderNode,
+}
+
+/// The meshlet meshes & colliders generated for the primitives of a gLTF's meshes.
+/// Each primitive's meshlet mesh & collider (for each shape) is only generated the first time it's used,
+/// so every node using the same gLTF mesh shares them.
+struct GltfPrimitiveCache<'a> {
+    gltf_index: usize,
+    gltf_meshes: &'a HashMap<Handle<GltfMesh>, Vec<(Mesh, Option<usize>, TerrainMeshSource)>>,
+    meshlet_mesh_indices: HashMap<(Handle<GltfMesh>, usize), Option<usize>>,
+    colliders:
+        HashMap<(Handle<GltfMesh>, usize, TerrainColliderShape), Option<SerialisedTerrainCollider>>,
+}
+
+impl<'a> GltfPrimitiveCache<'a> {
+    fn new(
+        gltf_index: usize,
+        gltf_meshes: &'a HashMap<Handle<GltfMesh>, Vec<(Mesh, Option<usize>, TerrainMeshSource)>>,
+    ) -> Self {
+        Self {
+            gltf_index,
+            gltf_meshes,
+            meshlet_mesh_indices: HashMap::new(),
+            colliders: HashMap::new(),
+        }
+    }
+
+    fn primitives(
+        &self,
+        gltf_mesh_handle: &Handle<GltfMesh>,
+    ) -> &'a [(Mesh, Option<usize>, TerrainMeshSource)] {
+        // Unwrap is safe, as every mesh of the gLTF is added before its nodes are processed
+        self.gltf_meshes.get(gltf_mesh_handle).unwrap()
+    }
+
+    /// Returns the meshlets of the mesh's primitives which have a material.
+    /// New meshlet meshes are added to `meshlet_meshes`.
+    fn meshlets(
+        &mut self,
+        gltf_mesh_handle: &Handle<GltfMesh>,
+        meshlet_meshes: &mut Vec<SerialisedMeshletMesh>,
+        settings: &TerrainProcessSettings,
+    ) -> Result<Vec<SerialisedMeshlet>, LoaderError> {
+        let mut meshlets = Vec::new();
+
+        for (mesh, material_index, mesh_source) in self.primitives(gltf_mesh_handle) {
+            let Some(material_index) = material_index else {
+                continue;
+            };
+
+            let key = (gltf_mesh_handle.clone(), mesh_source.primitive_index);
+
+            let mesh_index = match self.meshlet_mesh_indices.get(&key) {
+                Some(mesh_index) => *mesh_index,
+                None => {
+                    let mesh_index = process_mesh(
+                        mesh.clone(),
+                        true,
+                        TerrainColliderShape::None,
+                        mesh_source,
+                        settings,
+                    )?
+                    .1
+                    .map(|mesh| {
+                        meshlet_meshes.push(SerialisedMeshletMesh {
+                            label: gltf_meshlet_mesh_label(self.gltf_index, mesh_source),
+                            mesh,
+                        });
+                        meshlet_meshes.len() - 1
+                    });
+
+                    self.meshlet_mesh_indices.insert(key, mesh_index);
+                    mesh_index
+                }
+            };
+
+            meshlets.extend(mesh_index.map(|mesh_index| SerialisedMeshlet {
+                mesh_index,
+                material_index: *material_index,
+                primitive_index: mesh_source.primitive_index,
+            }));
+        }
+
+        Ok(meshlets)
+    }
+
+    /// Returns the colliders of the mesh's primitives with the given shape.
+    /// New collider shapes are added to `collider_shapes`.
+    fn colliders(
+        &mut self,
+        gltf_mesh_handle: &Handle<GltfMesh>,
+        collider_shape: TerrainColliderShape,
+        collider_shapes: &mut Vec<Collider>,
+        settings: &TerrainProcessSettings,
+    ) -> Result<Vec<SerialisedTerrainCollider>, LoaderError> {
+        let mut colliders = Vec::new();
+
+        for (mesh, _, mesh_source) in self.primitives(gltf_mesh_handle) {
+            let key = (
+                gltf_mesh_handle.clone(),
+                mesh_source.primitive_index,
+                collider_shape,
+            );
+
+            let collider = match self.colliders.get(&key) {
+                Some(collider) => collider.clone(),
+                None => {
+                    let collider = generate_collider(mesh, collider_shape, mesh_source, settings)?
+                        .map(|collider| collider.into_serialised(collider_shapes));
+
+                    self.colliders.insert(key, collider.clone());
+                    collider
+                }
+            };
+
+            colliders.extend(collider);
+        }
+
+        Ok(colliders)
+    }
+}
+
+/// Returns the sub-asset label of the meshlet mesh generated from a gLTF mesh primitive (see [`meshlet_mesh_label`]).
+fn gltf_meshlet_mesh_label(gltf_index: usize, mesh_source: &TerrainMeshSource) -> String {
+    meshlet_mesh_label(&format!(
+        "Gltf{0}/Mesh{1}/Primitive{2}",
+        gltf_index,
+        mesh_source.mesh_index.unwrap_or_default(),
+        mesh_source.primitive_index
+    ))
+}
+
+/// Looks up the meshlets & colliders of each instance from the cache (so instances of the same mesh share them),
+/// and computes its world-space bounds.
+/// New meshlet meshes & collider shapes are added to `meshlet_meshes` & `collider_shapes`.
+fn build_chunk_instances(
+    instances: Vec<WorldMeshInstance>,
+    primitive_cache: &mut GltfPrimitiveCache,
+    meshlet_meshes: &mut Vec<SerialisedMeshletMesh>,
+    collider_shapes: &mut Vec<Collider>,
+    settings: &TerrainProcessSettings,
+) -> Result<Vec<ChunkInstance>, LoaderError> {
+    let mut chunk_instances = Vec::with_capacity(instances.len());
+
+    for instance in instances {
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+
+        for aabb in primitive_cache
+            .primitives(&instance.gltf_mesh)
+            .iter()
+            .filter_map(|(mesh, _, _)| mesh.compute_aabb())
+        {
+            let (aabb_min, aabb_max) = (Vec3::from(aabb.min()), Vec3::from(aabb.max()));
+
+            for corner in 0..8 {
+                let corner = Vec3::select(
+                    BVec3::new(corner & 1 != 0, corner & 2 != 0, corner & 4 != 0),
+                    aabb_max,
+                    aabb_min,
+                );
+                let corner = instance.transform.transform_point(corner);
+                min = min.min(corner);
+                max = max.max(corner);
+            }
+        }
+
+        let meshes = if instance.meshlets {
+            primitive_cache.meshlets(&instance.gltf_mesh, meshlet_meshes, settings)?
+        } else {
+            Vec::new()
+        };
+
+        let colliders = primitive_cache.colliders(
+            &instance.gltf_mesh,
+            instance.collider_shape,
+            collider_shapes,
+            settings,
+        )?;
+
+        chunk_instances.push(ChunkInstance {
+            min,
+            max,
+            streaming_chunk: instance.streaming_chunk,
+            meshlet_node: SerialisedMeshletNode {
+                path: instance.path,
+                meshes,
+                transform: instance.transform,
+                children: Vec::new(),
+            },
+            collider_node: SerialisedTerrainColliderNode {
+                colliders,
+                collider_shape: instance.collider_shape,
+                physics_material: instance.physics_material,
+                collision_group: instance.collision_group,
+                transform: instance.transform,
+                children: Vec::new(),
+            },
+        });
+    }
+
+    Ok(chunk_instances)
+}
+
+/// Splits the world-space meshes into chunks, then generates the meshlets & colliders for each chunk.
+/// Instances are added whole to the chunk containing their centre (or their streaming chunk).
+/// The chunks' meshlet meshes & collider shapes are added to `meshlet_meshes` & `collider_shapes`.
+fn build_chunks(
+    world_meshes: Vec<WorldMesh>,
+    instances: Vec<ChunkInstance>,
+    chunk_size: f32,
+    meshlet_meshes: &mut Vec<SerialisedMeshletMesh>,
+    collider_shapes: &mut Vec<Collider>,
+    settings: &TerrainProcessSettings,
+) -> Result<Vec<SerialisedTerrainChunk>, LoaderError> {
+    if chunk_size <= 0.0 {
+        return Err(LoaderError::InvalidChunkSize(chunk_size));
+    }
+
+    debug!("Splitting terrain into chunks...");
+
+    let mut chunk_meshes: HashMap<IVec2, Vec<WorldMesh>> = HashMap::new();
+
+    for world_mesh in world_meshes {
+        let split_meshes = match world_mesh.streaming_chunk {
+            Some(coord) => vec![(coord, world_mesh.mesh.clone())],
+            None => split_mesh_into_chunks(&world_mesh.mesh, chunk_size),
+        };
+
+        for (coord, chunk_mesh) in split_meshes {
+            chunk_meshes.entry(coord).or_default().push(WorldMesh {
+                mesh: chunk_mesh,
+                source: TerrainMeshSource {
+                    chunk: Some(coord),
+                    ..world_mesh.source.clone()
+                },
+                ..world_mesh
+            });
+        }
+    }
+
+    let mut chunk_instances: HashMap<IVec2, Vec<ChunkInstance>> = HashMap::new();
+
+    for instance in instances {
+        let coord = instance.streaming_chunk.unwrap_or_else(|| {
+            (((instance.min + instance.max) / 2.0).xz() / chunk_size)
+                .floor()
+                .as_ivec2()
+        });
+
+        chunk_instances.entry(coord).or_default().push(instance);
+    }
+
+    let mut coords: Vec<IVec2> = chunk_meshes
+        .keys()
+        .chain(chunk_instances.keys())
+        .copied()
+        .collect::<HashSet<IVec2>>()
+        .into_iter()
+        .collect();
+    coords.sort_by_key(|coord| (coord.y, coord.x));
+
+    debug!("Terrain split into {} chunks", coords.len());
+
+    let mut chunks = Vec::with_capacity(coords.len());
+
+    for coord in coords {
+        let mut min = Vec3::splat(f32::MAX);
+        let mut max = Vec3::splat(f32::MIN);
+        let mut meshes = Vec::new();
+        let mut colliders = Vec::new();
+        // Colliders with a physics material or collision group are grouped into child nodes
+        let mut collider_children: Vec<SerialisedTerrainColliderNode> = Vec::new();
+
+        for (primitive_index, world_mesh) in chunk_meshes
+            .remove(&coord)
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+        {
+            if let Some(aabb) = world_mesh.mesh.compute_aabb() {
+                min = min.min(aabb.min().into());
+                max = max.max(aabb.max().into());
+            }
+
+            let (collider, meshlet) = process_mesh(
+                world_mesh.mesh,
+                world_mesh.material_index.is_some(),
+                world_mesh.collider_shape,
+                &world_mesh.source,
+                settings,
+            )?;
+
+            if let Some(collider) = collider {
+                let collider = collider.into_serialised(collider_shapes);
+
+                if world_mesh.physics_material.is_none() && world_mesh.collision_group.is_none() {
+                    colliders.push(collider);
+                } else if let Some(child) = collider_children.iter_mut().find(|child| {
+                    child.physics_material == world_mesh.physics_material
+                        && child.collision_group == world_mesh.collision_group
+                }) {
+                    child.colliders.push(collider);
+                } else {
+                    collider_children.push(SerialisedTerrainColliderNode {
+                        colliders: vec![collider],
+                        collider_shape: world_mesh.collider_shape,
+                        physics_material: world_mesh.physics_material,
+                        collision_group: world_mesh.collision_group,
+                        transform: Transform::IDENTITY,
+                        children: Vec::new(),
+                    });
+                }
+            }
+
+            if let Some((mesh, material_index)) = meshlet.zip(world_mesh.material_index) {
+                meshlet_meshes.push(SerialisedMeshletMesh {
+                    label: meshlet_mesh_label(&format!(
+                        "{0}/Primitive{1}",
+                        TerrainChunk::label(coord),
+                        primitive_index
+                    )),
+                    mesh,
+                });
+                meshes.push(SerialisedMeshlet {
+                    mesh_index: meshlet_meshes.len() - 1,
+                    material_index,
+                    primitive_index,
+                });
+            }
+        }
+
+        let mut meshlet_children = Vec::new();
+
+        for instance in chunk_instances.remove(&coord).unwrap_or_default() {
+            min = min.min(instance.min);
+            max = max.max(instance.max);
+            meshlet_children.push(instance.meshlet_node);
+            collider_children.push(instance.collider_node);
+        }
+
+        chunks.push(SerialisedTerrainChunk {
+            coord,
+            min,
+            max,
+            meshlet_node: SerialisedMeshletNode {
+                path: TerrainChunk::label(coord),
+                meshes,
+                transform: Transform::IDENTITY,
+                children: meshlet_children,
+            },
+            collider_node: SerialisedTerrainColliderNode {
+                colliders,
+                collider_shape: settings.collider_shape,
+                physics_material: None,
+                collision_group: None,
+                transform: Transform::IDENTITY,
+                children: collider_children,
+            },
+        });
+    }
+
+    Ok(chunks)
+}
+
+/// Reads the heightmap image (or raw heightmap) referenced by the terrain RON file.
+async fn load_heightmap(
+    source: &SerialisedHeightmap,
+    load_context: &mut LoadContext<'_>,
+) -> Result<Heightmap, LoaderError> {
+    let heightmap = if let Some(raw_size) = source.raw_size {
+        let bytes = load_context.read_asset_bytes(&source.path).await?;
+        Heightmap::from_raw_r16(&bytes, raw_size)
+    } else {
+        let image_asset = load_context
+            .loader()
+            .with_settings(|settings: &mut ImageLoaderSettings| settings.is_srgb = false)
+            .direct()
+            .load::<Image>(&source.path)
+            .await?;
+        Heightmap::from_image(image_asset.get())
+    };
+
+    debug!("Heightmap loaded");
+
+    heightmap.ok_or_else(|| LoaderError::UnsupportedHeightmap(source.path.clone()))
+}
+
+/// Returns the indices of the root nodes of the given scene (or the default scene if no name is given).
+/// Falls back to every node without a parent if the gLTF source (or a default scene) is unavailable.
+fn gltf_root_node_indices(
+    model_asset: &LoadedAsset<Gltf>,
+    scene_name: Option<&str>,
+) -> Result<Vec<usize>, LoaderError> {
+    let gltf = model_asset.get();
+
+    if let Some(source) = &gltf.source {
+        let scene = match scene_name {
+            Some(scene_name) => Some(
+                source
+                    .scenes()
+                    .find(|scene| scene.name() == Some(scene_name))
+                    .ok_or_else(|| LoaderError::SceneNotFound(scene_name.to_string()))?,
+            ),
+            None => source.default_scene().or_else(|| source.scenes().next()),
+        };
+
+        if let Some(scene) = scene {
+            return Ok(scene.nodes().map(|node| node.index()).collect());
+        }
+    } else if let Some(scene_name) = scene_name {
+        return Err(LoaderError::MissingGltfSource(scene_name.to_string()));
+    }
+
+    let child_indices: HashSet<usize> = (0..gltf.nodes.len())
+        .filter_map(|node_index| model_asset.get_labeled(format!("Node{}", node_index)))
+        .filter_map(|gltf_node_asset| gltf_node_asset.get::<GltfNode>())
+        .flat_map(|gltf_node| gltf_node.children.iter().map(|child| child.index))
+        .collect();
+
+    Ok((0..gltf.nodes.len())
+        .filter(|node_index| !child_indices.contains(node_index))
+        .collect())
+}
+
+/// Processing options of each node in a gLTF scene, resolved from naming conventions, extras & RON overrides.
+#[derive(Default)]
+struct GltfNodeOptions {
+    nodes: HashMap<usize, NodeOptions>,
+    /// Number of nodes using each mesh.
+    mesh_node_counts: HashMap<Handle<GltfMesh>, usize>,
+}
+
+struct NodeOptions {
+    /// False for collision nodes (see [`collision_proxy_target`]) & `no_meshlet` nodes, which are only used for physics.
+    meshlets: bool,
+    collider_shape: TerrainColliderShape,
+    physics_material: Option<TerrainPhysicsMaterial>,
+    collision_group: Option<u32>,
+    streaming_chunk: Option<IVec2>,
+}
+
+impl GltfNodeOptions {
+    fn from_root_nodes(
+        root_nodes: &[&GltfNode],
+        mesh_extras: &HashMap<Handle<GltfMesh>, TerrainGltfExtras>,
+        ron: &SerialisedTerrainDetails,
+        settings: &TerrainProcessSettings,
+    ) -> Result<Self, LoaderError> {
+        let mut node_options = Self::default();
+        node_options.add_siblings(root_nodes.iter().copied(), mesh_extras, ron, settings)?;
+        Ok(node_options)
+    }
+
+    fn add_siblings<'a>(
+        &mut self,
+        siblings: impl Iterator<Item = &'a GltfNode> + Clone,
+        mesh_extras: &HashMap<Handle<GltfMesh>, TerrainGltfExtras>,
+        ron: &SerialisedTerrainDetails,
+        settings: &TerrainProcessSettings,
+    ) -> Result<(), LoaderError> {
+        let collision_targets: HashSet<&str> = siblings
+            .clone()
+            .filter_map(|gltf_node| collision_proxy_target(&gltf_node.name))
+            .collect();
+
+        for gltf_node in siblings {
+            let mut extras = TerrainGltfExtras::from_gltf_extras(gltf_node.extras.as_ref())
+                .map_err(|error| LoaderError::InvalidGltfExtras {
+                    node: gltf_node.name.clone(),
+                    error,
+                })?;
+
+            // Node extras take priority over those of its mesh
+            if let Some(mesh_extras) = gltf_node
+                .mesh
+                .as_ref()
+                .and_then(|gltf_mesh_handle| mesh_extras.get(gltf_mesh_handle))
+            {
+                extras = extras.or(mesh_extras);
+            }
+
+            let is_collision_node = collision_proxy_target(&gltf_node.name).is_some();
+            let meshlets = !is_collision_node && !extras.no_meshlet.unwrap_or(false);
+
+            // Collision nodes are convex unless overridden (following the `UCX_` convention)
+            let default_collider_shape = if is_collision_node {
+                TerrainColliderShape::ConvexHull
+            } else {
+                settings.collider_shape
+            };
+
+            // Sibling collision nodes replace the collider, otherwise RON overrides take priority over extras
+            let collider_shape = if collision_targets.contains(gltf_node.name.as_str()) {
+                TerrainColliderShape::None
+            } else {
+                ron.collider_overrides
+                    .get(&gltf_node.name)
+                    .copied()
+                    .or(extras.collider)
+                    .unwrap_or(default_collider_shape)
+            };
+
+            let physics_material = match &extras.physics_material {
+                Some(material) => Some(*ron.physics_materials.get(material).ok_or_else(|| {
+                    LoaderError::MissingPhysicsMaterial {
+                        node: gltf_node.name.clone(),
+                        material: material.clone(),
+                    }
+                })?),
+                None => None,
+            };
+
+            if let Some(group) = extras.collision_group.filter(|group| *group >= 32) {
+                return Err(LoaderError::InvalidCollisionGroup {
+                    node: gltf_node.name.clone(),
+                    group,
+                });
+            }
+
+            if let Some(gltf_mesh_handle) = &gltf_node.mesh {
+                *self
+                    .mesh_node_counts
+                    .entry(gltf_mesh_handle.clone())
+                    .or_default() += 1;
+            }
+
+            self.nodes.insert(
+                gltf_node.index,
+                NodeOptions {
+                    meshlets,
+                    collider_shape,
+                    physics_material,
+                    collision_group: extras.collision_group,
+                    streaming_chunk: extras.streaming_chunk,
+                },
+            );
+
+            self.add_siblings(gltf_node.children.iter(), mesh_extras, ron, settings)?;
+        }
+
+        Ok(())
+    }
+
+    fn get(&self, gltf_node: &GltfNode) -> &NodeOptions {
+        // Unwrap is safe, as every node in the scene is added above
+        self.nodes.get(&gltf_node.index).unwrap()
+    }
+
+    /// Whether the mesh is used by more than one node (so is instanced instead of split when chunking).
+    fn is_instanced(&self, gltf_mesh_handle: &Handle<GltfMesh>) -> bool {
+        self.mesh_node_counts
+            .get(gltf_mesh_handle)
+            .is_some_and(|count| *count > 1)
+    }
+}
+
 #[derive(Default)]
 pub struct TerrainDetailsAssetLoader;
 
@@ -266,39 +1703,112 @@ impl AssetLoader for TerrainDetailsAssetLoader {
         _settings: &'a Self::Settings,
         load_context: &'a mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
-        let mut compressed_bytes = Vec::new();
-        reader.read_to_end(&mut compressed_bytes).await?;
-        let bytes = zstd::stream::decode_all(compressed_bytes.as_slice())?;
-        let bin: ProcessedTerrainDetails = postcard::from_bytes(&bytes)?;
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let (_, bin) = decode_processed_terrain(&bytes)?;
+
+        // Checked up front, so loading the nodes can index the meshes, materials & collider shapes directly
+        bin.validate_meshlet_indices()?;
+        bin.validate_collider_shape_indices()?;
 
         fn meshlet_gltf_load_settings(settings: &mut GltfLoaderSettings) {
             settings.load_meshes = RenderAssetUsages::empty();
             settings.load_materials = RenderAssetUsages::RENDER_WORLD;
         }
 
-        // Get a handle to the gLTF (seems to be necessary to keep material handles working after cloning below)
-        let gltf_handle = load_context
-            .loader()
-            .with_settings(meshlet_gltf_load_settings)
-            .load(&bin.gltf_path);
+        // Heightmap-only terrain doesn't have any gLTFs
+        let mut gltf_handles = Vec::with_capacity(bin.gltf_paths.len());
+        let mut model_assets = Vec::with_capacity(bin.gltf_paths.len());
+
+        for gltf_path in &bin.gltf_paths {
+            // Get a handle to the gLTF (seems to be necessary to keep material handles working after cloning below)
+            gltf_handles.push(
+                load_context
+                    .loader()
+                    .with_settings(meshlet_gltf_load_settings)
+                    .load(gltf_path),
+            );
+
+            // Load the gLTF directly so the material handles can be retrieved
+            // Uses the above settings to avoid loading the mesh data
+            model_assets.push(
+                load_context
+                    .loader()
+                    .with_settings(meshlet_gltf_load_settings)
+                    .direct()
+                    .load::<Gltf>(gltf_path)
+                    .await?,
+            );
+        }
 
-        // Load the gLTF directly so the material handles can be retrieved
-        // Uses the above settings to avoid loading the mesh data
-        let model_asset = load_context
-            .loader()
-            .with_settings(meshlet_gltf_load_settings)
-            .direct()
-            .load::<Gltf>(bin.gltf_path)
-            .await?;
+        let mut materials = Vec::with_capacity(bin.materials.len());
+
+        for (index, material) in bin.materials.iter().enumerate() {
+            let material_handle = match material {
+                SerialisedMaterial::Gltf { source, material } => model_assets
+                    .get(*source)
+                    .and_then(|model_asset| model_asset.get().materials.get(*material))
+                    .cloned()
+                    .map(TerrainMaterialHandle::from)
+                    .ok_or(LoaderError::MissingGltfMaterial {
+                        gltf: *source,
+                        material: *material,
+                    })?,
+                SerialisedMaterial::Standard(material) => material
+                    .load(format!("Material{}", index), load_context)
+                    .into(),
+                SerialisedMaterial::Terrain(material) => material
+                    .load(format!("Material{}", index), load_context)
+                    .into(),
+            };
+
+            materials.push(material_handle);
+        }
 
-        let gltf: &Gltf = model_asset.get();
+        // Each unique meshlet mesh is added once, and its handle shared by every node using it
+        let meshlet_meshes: Vec<Handle<MeshletMesh>> = bin
+            .meshlet_meshes
+            .into_iter()
+            .map(|meshlet_mesh| {
+                load_context.add_labeled_asset(meshlet_mesh.label, meshlet_mesh.mesh)
+            })
+            .collect();
+
+        let meshlet_scene = MeshletScene::load(bin.meshlet_nodes, &meshlet_meshes, &materials);
+
+        let chunks = bin
+            .chunks
+            .into_iter()
+            .map(|chunk| {
+                let chunk = TerrainChunk {
+                    coord: chunk.coord,
+                    min: chunk.min,
+                    max: chunk.max,
+                    meshlet_node: MeshletNode::load(
+                        chunk.meshlet_node,
+                        &meshlet_meshes,
+                        &materials,
+                    ),
+                    collider_node: TerrainColliderNode::load(
+                        chunk.collider_node,
+                        &bin.collider_shapes,
+                    ),
+                };
 
-        let meshlet_scene = MeshletScene::load(bin.meshlet_nodes, gltf, load_context);
+                load_context.add_labeled_asset(TerrainChunk::label(chunk.coord), chunk)
+            })
+            .collect();
 
         Ok(TerrainDetails {
-            _gltf_handle: gltf_handle,
+            gltf_handles,
             meshlet_scene,
-            colliders: bin.colliders,
+            colliders: bin
+                .colliders
+                .into_iter()
+                .map(|node| TerrainColliderNode::load(node, &bin.collider_shapes))
+                .collect(),
+            chunk_size: bin.chunk_size,
+            chunks,
         })
     }
 
@@ -315,7 +1825,7 @@ pub struct ProcessedTerrainSaver;
 
 impl AssetSaver for ProcessedTerrainSaver {
     type Asset = ProcessedTerrainDetails;
-    type Settings = ();
+    type Settings = TerrainSaverSettings;
     type OutputLoader = TerrainDetailsAssetLoader;
     type Error = SaverError;
 
@@ -323,13 +1833,17 @@ impl AssetSaver for ProcessedTerrainSaver {
         &'a self,
         writer: &'a mut Writer,
         asset: SavedAsset<'a, Self::Asset>,
-        _settings: &'a Self::Settings,
+        settings: &'a Self::Settings,
     ) -> Result<(), Self::Error> {
-        let bytes = postcard::to_allocvec(asset.get())?;
-
-        // Compress using ZSTD
-        let compressed_bytes = zstd::encode_all(bytes.as_slice(), 19)?;
-        writer.write_all(&compressed_bytes).await?;
+        let step_start = Instant::now();
+        let bytes = encode_processed_terrain(asset.get(), settings.compression_level)?;
+        debug!(
+            "Processed terrain asset encoded ({0} bytes) in {1:?}",
+            bytes.len(),
+            step_start.elapsed()
+        );
+
+        writer.write_all(&bytes).await?;
         debug!("Processed terrain asset written to disk");
         Ok(())
     }
@@ -339,10 +1853,91 @@ impl AssetSaver for ProcessedTerrainSaver {
 // RESOURCES
 //****************************************************************************
 
+/// How long each step of processing each terrain took (keyed by the terrain's path, relative to the assets folder).
+/// Shared with [`ProcessedTerrainDetailsAssetLoader`], so `terrain-process` can report the steps once processing finishes.
+#[derive(Resource, Clone, Default)]
+pub struct TerrainProcessTimings(Arc<Mutex<HashMap<PathBuf, Vec<(String, Duration)>>>>);
+
+impl TerrainProcessTimings {
+    /// Forgets the steps of any previous processing of the terrain.
+    fn clear(&self, terrain_path: &Path) {
+        self.0.lock().unwrap().remove(terrain_path);
+    }
+
+    fn record(&self, terrain_path: &Path, step: String, duration: Duration) {
+        self.0
+            .lock()
+            .unwrap()
+            .entry(terrain_path.to_path_buf())
+            .or_default()
+            .push((step, duration));
+    }
+
+    /// The steps of processing the terrain, in the order they finished.
+    /// Empty if the terrain wasn't processed (e.g. it was already up to date).
+    pub fn get(&self, terrain_path: &Path) -> Vec<(String, Duration)> {
+        self.0
+            .lock()
+            .unwrap()
+            .get(terrain_path)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// The terrain file to load (relative to the assets folder).
+/// Changing this whilst in-game unloads the current terrain and loads the new one.
+#[derive(Resource, Clone, Debug)]
+pub struct TerrainSelection {
+    pub path: String,
+}
+
+impl Default for TerrainSelection {
+    fn default() -> Self {
+        Self {
+            path: TERRAIN_PATH.to_string(),
+        }
+    }
+}
+
+impl TerrainSelection {
+    /// Reads the terrain from the `--terrain <path>` argument, or the [`TERRAIN_ENV_VAR`] environment variable.
+    /// Falls back to [`TERRAIN_PATH`].
+    pub fn from_args_or_env() -> Self {
+        let mut args = std::env::args().skip(1);
+        let mut path = None;
+
+        while let Some(arg) = args.next() {
+            if arg == "--terrain" {
+                path = args.next();
+            } else if let Some(value) = arg.strip_prefix("--terrain=") {
+                path = Some(value.to_string());
+            }
+        }
+
+        match path.or_else(|| std::env::var(TERRAIN_ENV_VAR).ok()) {
+            Some(path) => Self { path },
+            None => Self::default(),
+        }
+    }
+}
+
 #[derive(Resource)]
 pub struct TerrainStartupManager {
     pub state: AssetLoadState,
+    /// The path of the terrain being loaded (from TerrainSelection).
+    pub terrain_path: String,
     pub terrain_detail_handle: Handle<TerrainDetails>,
+    /// Why the terrain failed to load (set once the state is Failed).
+    pub error: Option<String>,
+}
+
+#[derive(Resource, Default, Clone, Debug)]
+pub struct TerrainStartupSettings {
+    /// Exit with a non-zero code if the terrain fails to load (e.g. when running headless), instead of offering a retry.
+    pub exit_on_failure: bool,
+    /// Show the TerrainLoadProgress overlay whilst in the Startup GameState.
+    pub show_progress_overlay: bool,
 }
 
 #[derive(Eq, PartialEq, Copy, Clone)]
@@ -356,17 +1951,37 @@ pub enum AssetLoadState {
 // ENTER SYSTEMS - GAMESTATE:STARTUP
 //****************************************************************************
 
-/// Starts loading the terrain.
+/// Starts loading the selected terrain (or reloads it, when retrying after a failure).
+/// Replacing the manager drops the handle to any previous terrain, so it's unloaded.
 pub fn asset_startup_enter_system(
     mut commands: Commands,
     // Resources
     asset_server: Res<AssetServer>,
+    selection: Res<TerrainSelection>,
+    manager: Option<Res<TerrainStartupManager>>,
 ) {
-    debug!("Starting to load terrain");
+    debug!("Starting to load terrain {}", selection.path);
+
+    // Loading restarts the terrain file if it failed itself
+    let terrain_detail_handle: Handle<TerrainDetails> = asset_server.load(&selection.path);
+
+    // But if the file loaded and one of its dependencies (e.g. a gLTF) failed, it has to be reloaded to retry them
+    if manager.is_some_and(|manager| manager.state == AssetLoadState::Failed)
+        && matches!(
+            asset_server.load_state(terrain_detail_handle.id()),
+            LoadState::Loaded
+        )
+        && asset_server.recursive_dependency_load_state(terrain_detail_handle.id())
+            == RecursiveDependencyLoadState::Failed
+    {
+        asset_server.reload(&selection.path);
+    }
 
     commands.insert_resource(TerrainStartupManager {
         state: AssetLoadState::Loading,
-        terrain_detail_handle: asset_server.load(TERRAIN_PATH),
+        terrain_path: selection.path.clone(),
+        terrain_detail_handle,
+        error: None,
     });
 }
 
@@ -381,11 +1996,20 @@ pub fn finalise_startup_system(
     asset_server: Res<AssetServer>,
     // Assets
     terrain_details_assets: Res<Assets<TerrainDetails>>,
+    // Events
+    mut load_failed_events: EventReader<UntypedAssetLoadFailedEvent>,
+    // Local
+    mut load_failures: Local<Vec<UntypedAssetLoadFailedEvent>>,
 ) {
     if manager.state != AssetLoadState::Loading {
+        load_failed_events.clear();
+        load_failures.clear();
         return;
     }
 
+    // Kept until the terrain fails, as its dependencies (e.g. a missing gLTF) can fail before it has loaded
+    load_failures.extend(load_failed_events.read().cloned());
+
     // Check the status of the Terrain detail asset
     let terrain_details = match asset_server
         .recursive_dependency_load_state(&manager.terrain_detail_handle)
@@ -400,7 +2024,23 @@ pub fn finalise_startup_system(
         }
 
         RecursiveDependencyLoadState::Failed => {
-            error!("Error whilst loading terrain details");
+            // Only failures of the terrain or its dependencies (ignoring unrelated assets loading at the same time)
+            let mut terrain_asset_ids = vec![manager.terrain_detail_handle.id().untyped()];
+            if let Some(terrain_details) =
+                terrain_details_assets.get(&manager.terrain_detail_handle)
+            {
+                terrain_asset_ids.extend(terrain_details.asset_ids());
+            }
+
+            // Keep the first error, as it's usually the cause of any others (e.g. a missing gLTF causing the terrain to fail)
+            let error = load_failures
+                .iter()
+                .find(|event| terrain_asset_ids.contains(&event.id))
+                .map(|event| format!("{0}: {1}", event.path, event.error))
+                .unwrap_or_else(|| "Unknown error".to_string());
+
+            error!("Error whilst loading terrain details: {}", error);
+            manager.error = Some(error);
             manager.state = AssetLoadState::Failed;
             return;
         }
@@ -410,16 +2050,253 @@ pub fn finalise_startup_system(
 
     let terrain_entity = terrain_details.meshlet_scene.spawn(&mut commands);
 
+    // Despawned when leaving InGame (e.g. to switch terrain)
     commands
         .entity(terrain_entity)
-        .insert(Name::from("Terrain"))
+        .insert((Name::from("Terrain"), StateScoped(GameState::InGame)))
         .with_children(|parent| {
             for collider_node in &terrain_details.colliders {
                 collider_node.spawn(parent);
             }
         });
 
+    // Chunks are spawned around the player once in-game
+    if !terrain_details.chunks.is_empty() {
+        commands.insert_resource(TerrainChunkStreamer {
+            terrain_entity,
+            spawned_chunks: HashMap::new(),
+        });
+    }
+
     debug!("Terrain loaded successfully");
 
     manager.state = AssetLoadState::Loaded;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::mesh::GenerateTangentsError;
+
+    #[test]
+    fn process_mesh_errors_identify_primitive() {
+        let mut mesh = Plane3d::default().mesh().size(4.0, 4.0).build();
+        mesh.remove_attribute(Mesh::ATTRIBUTE_UV_0);
+
+        let mesh_source = TerrainMeshSource {
+            gltf_path: Some("terrain.gltf".to_string()),
+            mesh_index: Some(2),
+            primitive_index: 1,
+            node_name: Some("Cliffs".to_string()),
+            chunk: None,
+        };
+
+        let settings = TerrainProcessSettings::default();
+        let result = process_mesh(mesh, true, settings.collider_shape, &mesh_source, &settings);
+
+        match result {
+            Err(LoaderError::TangentGeneration {
+                mesh,
+                error: GenerateTangentsError::MissingVertexAttribute(_),
+            }) => {
+                assert_eq!(mesh.mesh_index, Some(2));
+                assert_eq!(mesh.primitive_index, 1);
+                assert_eq!(mesh.node_name.as_deref(), Some("Cliffs"));
+            }
+            _ => panic!("Expected a tangent generation error"),
+        }
+    }
+
+    #[test]
+    fn unreadable_headers_are_incompatible() {
+        assert!(matches!(
+            decompress_processed_terrain(b"(gltf_path: \"terrain.gltf\")"),
+            Err(LoaderError::IncompatibleTerrainFile(_))
+        ));
+
+        let mut bytes = TERRAIN_FILE_MAGIC.to_vec();
+        bytes.extend([0xff; 3]);
+
+        assert!(matches!(
+            decompress_processed_terrain(&bytes),
+            Err(LoaderError::IncompatibleTerrainFile(_))
+        ));
+    }
+
+    #[test]
+    fn instances_are_added_whole_to_their_chunk() {
+        let instance = |path: &str, min: Vec3, max: Vec3, streaming_chunk: Option<IVec2>| {
+            let transform = Transform::from_translation((min + max) / 2.0);
+
+            ChunkInstance {
+                min,
+                max,
+                streaming_chunk,
+                meshlet_node: SerialisedMeshletNode {
+                    path: path.to_string(),
+                    meshes: Vec::new(),
+                    transform,
+                    children: Vec::new(),
+                },
+                collider_node: SerialisedTerrainColliderNode {
+                    colliders: vec![SerialisedTerrainCollider {
+                        shape_index: 0,
+                        transform: Transform::IDENTITY,
+                    }],
+                    collider_shape: TerrainColliderShape::TriMesh,
+                    physics_material: None,
+                    collision_group: None,
+                    transform,
+                    children: Vec::new(),
+                },
+            }
+        };
+
+        let instances = vec![
+            // Overlaps chunks (0, 0) & (1, 0), but its centre is in (1, 0)
+            instance(
+                "Node0",
+                Vec3::new(3.0, 0.0, 1.0),
+                Vec3::new(6.0, 1.0, 2.0),
+                None,
+            ),
+            instance(
+                "Node1",
+                Vec3::new(5.0, 0.0, 1.0),
+                Vec3::new(7.0, 2.0, 3.0),
+                None,
+            ),
+            instance(
+                "Node2",
+                Vec3::new(1.0, 0.0, 1.0),
+                Vec3::new(2.0, 1.0, 2.0),
+                Some(IVec2::new(0, -1)),
+            ),
+        ];
+
+        let chunks = build_chunks(
+            Vec::new(),
+            instances,
+            4.0,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &TerrainProcessSettings::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.coord).collect::<Vec<_>>(),
+            vec![IVec2::new(0, -1), IVec2::new(1, 0)]
+        );
+
+        let chunk = &chunks[1];
+        assert_eq!(chunk.min, Vec3::new(3.0, 0.0, 1.0));
+        assert_eq!(chunk.max, Vec3::new(7.0, 2.0, 3.0));
+        assert_eq!(
+            chunk
+                .meshlet_node
+                .children
+                .iter()
+                .map(|node| node.path.as_str())
+                .collect::<Vec<_>>(),
+            vec!["Node0", "Node1"]
+        );
+        // Instances keep their own transform, and share the collider shape
+        assert_eq!(chunk.collider_node.children.len(), 2);
+        assert_eq!(
+            chunk.collider_node.children[1].transform.translation,
+            Vec3::new(6.0, 1.0, 2.0)
+        );
+        assert!(chunk
+            .collider_node
+            .children
+            .iter()
+            .all(|node| node.colliders[0].shape_index == 0));
+    }
+
+    #[test]
+    fn instances_in_a_chunk_share_meshlet_mesh_and_collider() {
+        let gltf_mesh_handle: Handle<GltfMesh> = Handle::weak_from_u128(1);
+        let mesh = Plane3d::default()
+            .mesh()
+            .size(2.0, 2.0)
+            .subdivisions(3)
+            .build();
+        let mesh_source = TerrainMeshSource {
+            gltf_path: Some("props.gltf".to_string()),
+            mesh_index: Some(0),
+            primitive_index: 0,
+            node_name: Some("Rock".to_string()),
+            chunk: None,
+        };
+
+        let mut gltf_meshes = HashMap::new();
+        gltf_meshes.insert(gltf_mesh_handle.clone(), vec![(mesh, Some(0), mesh_source)]);
+
+        let instance = |path: &str, translation: Vec3| WorldMeshInstance {
+            path: path.to_string(),
+            gltf_mesh: gltf_mesh_handle.clone(),
+            transform: Transform::from_translation(translation),
+            meshlets: true,
+            collider_shape: TerrainColliderShape::TriMesh,
+            physics_material: None,
+            collision_group: None,
+            streaming_chunk: None,
+        };
+
+        let settings = TerrainProcessSettings::default();
+        let mut primitive_cache = GltfPrimitiveCache::new(0, &gltf_meshes);
+        let mut meshlet_meshes = Vec::new();
+        let mut collider_shapes = Vec::new();
+
+        let instances = build_chunk_instances(
+            vec![
+                instance("Node0", Vec3::new(2.0, 0.0, 2.0)),
+                instance("Node1", Vec3::new(5.0, 1.0, 2.0)),
+            ],
+            &mut primitive_cache,
+            &mut meshlet_meshes,
+            &mut collider_shapes,
+            &settings,
+        )
+        .unwrap();
+
+        let chunks = build_chunks(
+            Vec::new(),
+            instances,
+            8.0,
+            &mut meshlet_meshes,
+            &mut collider_shapes,
+            &settings,
+        )
+        .unwrap();
+
+        // One meshlet mesh & collider shape, shared by both instances
+        assert_eq!(meshlet_meshes.len(), 1);
+        assert_eq!(meshlet_meshes[0].label, "Meshlet/Gltf0/Mesh0/Primitive0");
+        assert_eq!(collider_shapes.len(), 1);
+
+        assert_eq!(chunks.len(), 1);
+        let chunk = &chunks[0];
+        assert_eq!(chunk.coord, IVec2::ZERO);
+        assert!(chunk.meshlet_node.meshes.is_empty());
+        assert_eq!(chunk.meshlet_node.children.len(), 2);
+        assert!(chunk
+            .meshlet_node
+            .children
+            .iter()
+            .all(|node| node.meshes.len() == 1 && node.meshes[0].mesh_index == 0));
+        assert_eq!(chunk.collider_node.children.len(), 2);
+        assert!(chunk
+            .collider_node
+            .children
+            .iter()
+            .all(|node| node.colliders.len() == 1 && node.colliders[0].shape_index == 0));
+        assert_eq!(
+            chunk.meshlet_node.children[1].transform.translation,
+            Vec3::new(5.0, 1.0, 2.0)
+        );
+        assert!(chunk.min.abs_diff_eq(Vec3::new(1.0, 0.0, 1.0), 1e-5));
+        assert!(chunk.max.abs_diff_eq(Vec3::new(6.0, 1.0, 3.0), 1e-5));
+    }
+}
@@ -0,0 +1,60 @@
+//! Prints the contents of processed terrain files.
+//!
+//! ```sh
+//! cargo run --bin terrain-inspect -- [--json] imported_assets/Default/default.terrain.bin
+//! ```
+
+use meshlet_terrain_testing::inspect::*;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut json = false;
+    let mut paths = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            _ => paths.push(arg),
+        }
+    }
+
+    if paths.is_empty() {
+        eprintln!("Usage: terrain-inspect [--json] <processed .terrain.bin files>");
+        return ExitCode::FAILURE;
+    }
+
+    let mut exit_code = ExitCode::SUCCESS;
+    let mut json_reports = Vec::new();
+
+    for path in &paths {
+        let report = std::fs::read(path)
+            .map_err(|err| err.to_string())
+            .and_then(|bytes| TerrainReport::from_bytes(&bytes).map_err(|err| err.to_string()));
+
+        match report {
+            Ok(report) if json => json_reports.push(report),
+            Ok(report) => println!("{0}\n{1}", path, report),
+            Err(err) => {
+                eprintln!("Failed to inspect {0}: {1}", path, err);
+                exit_code = ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if json {
+        let output = match json_reports.as_slice() {
+            [report] if paths.len() == 1 => serde_json::to_string_pretty(report),
+            reports => serde_json::to_string_pretty(reports),
+        };
+
+        match output {
+            Ok(output) => println!("{}", output),
+            Err(err) => {
+                eprintln!("Failed to write JSON: {}", err);
+                exit_code = ExitCode::FAILURE;
+            }
+        }
+    }
+
+    exit_code
+}
@@ -0,0 +1,232 @@
+//! Headless terrain processing, for build machines without a window or GPU.
+//!
+//! ```sh
+//! cargo run --bin terrain-process -- [--verify <terrain file, relative to the assets folder>]...
+//! ```
+//! The asset processor always processes the whole assets folder (as it removes the processed files of any assets it
+//! can't see), so sources can't be picked to process & positional arguments aren't accepted.
+//! `--verify` only selects which processed terrain files are checked & reported on (every `.terrain.bin` file in the
+//! assets folder if none are given).
+//! Exits with a non-zero code if any of the terrain files to verify fail to process.
+
+use bevy::app::ScheduleRunnerPlugin;
+use bevy::asset::io::file::FileAssetReader;
+use bevy::asset::io::AssetSourceId;
+use bevy::asset::processor::*;
+use bevy::asset::AsyncReadExt;
+use bevy::pbr::experimental::meshlet::MeshletPlugin;
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future};
+use meshlet_terrain_testing::assets::*;
+use meshlet_terrain_testing::*;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const ASSETS_FOLDER: &str = "assets";
+
+//****************************************************************************
+// RESOURCES
+//****************************************************************************
+
+#[derive(Resource)]
+struct TerrainProcessRun {
+    /// The terrain files to verify once processing has finished.
+    verify_sources: Vec<PathBuf>,
+    start: Instant,
+    /// Set once the processor has finished initialising (i.e. reading the transaction log & existing processed files).
+    processing_start: Option<Instant>,
+}
+
+//****************************************************************************
+// MAIN
+//****************************************************************************
+
+fn main() -> AppExit {
+    let Some(mut verify_sources) = parse_verify_sources(std::env::args().skip(1)) else {
+        eprintln!(
+            "Usage: terrain-process [--verify <terrain file, relative to the assets folder>]...\n\
+             Processes the whole assets folder, then verifies the given terrain files (or every .{TERRAIN_DETAILS_FILE_EXTENSION} file)."
+        );
+        return AppExit::error();
+    };
+
+    // Resolved the same way as the asset plugin's file_path (so it works outside of `cargo run`)
+    let assets_folder = FileAssetReader::get_base_path().join(ASSETS_FOLDER);
+
+    if verify_sources.is_empty() {
+        find_terrain_sources(&assets_folder, Path::new(""), &mut verify_sources);
+        verify_sources.sort();
+    }
+
+    if verify_sources.is_empty() {
+        eprintln!(
+            "No .{TERRAIN_DETAILS_FILE_EXTENSION} files found in `{}`",
+            assets_folder.display()
+        );
+        return AppExit::error();
+    }
+
+    App::new()
+        .add_plugins((
+            headless_plugins(AssetPlugin {
+                file_path: ASSETS_FOLDER.to_string(),
+                mode: AssetMode::Processed,
+                ..default()
+            })
+            .set(bevy::log::LogPlugin {
+                level: bevy::log::Level::INFO,
+                filter: LOG_LEVEL.to_string(),
+                ..default()
+            }),
+            ScheduleRunnerPlugin::run_loop(Duration::from_millis(10)),
+            MeshletPlugin,
+            TerrainAssetsPlugin,
+        ))
+        .insert_resource(TerrainProcessRun {
+            verify_sources,
+            start: Instant::now(),
+            processing_start: None,
+        })
+        .add_systems(Update, terrain_process_system)
+        .run()
+}
+
+//****************************************************************************
+// UPDATE SYSTEMS
+//****************************************************************************
+
+/// Waits for the asset processor to finish, then checks each terrain to verify was processed into a readable file.
+/// Prints how long each step of processing it took.
+/// Sends AppExit once finished.
+fn terrain_process_system(
+    // Resources
+    mut run: ResMut<TerrainProcessRun>,
+    processor: Res<AssetProcessor>,
+    timings: Res<TerrainProcessTimings>,
+    asset_server: Res<AssetServer>,
+    // Events
+    mut exit_events: EventWriter<AppExit>,
+) {
+    // The state is polled without waiting, as the processor holds its lock whilst processing
+    match block_on(future::poll_once(processor.get_state())) {
+        None | Some(ProcessorState::Initializing) => return,
+        Some(ProcessorState::Processing) => {
+            if run.processing_start.is_none() {
+                println!("Initialised processor in {:?}", run.start.elapsed());
+                run.processing_start = Some(Instant::now());
+            }
+            return;
+        }
+        Some(ProcessorState::Finished) => {}
+    }
+
+    let processing_start = run.processing_start.unwrap_or(run.start);
+    println!("Processed assets in {:?}", processing_start.elapsed());
+
+    // The default source's reader is gated on the processor, so it fails for assets which couldn't be processed
+    let reader = asset_server
+        .get_source(AssetSourceId::Default)
+        .expect("Default asset source should exist")
+        .reader();
+
+    let mut failures = 0;
+
+    for source in &run.verify_sources {
+        let verify_start = Instant::now();
+
+        let result = block_on(async {
+            let mut bytes = Vec::new();
+            let mut asset_reader = reader.read(source).await.map_err(|err| err.to_string())?;
+            asset_reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(|err| err.to_string())?;
+
+            decode_processed_terrain(&bytes)
+                .map(|(header, terrain)| (bytes.len(), header, terrain))
+                .map_err(|err| err.to_string())
+        });
+
+        match result {
+            Ok((size, header, terrain)) => {
+                println!(
+                    "OK     {0} (format v{1}, {2} bytes, {3} meshlet nodes, {4} collider nodes, {5} chunks) verified in {6:?}",
+                    source.display(),
+                    header.format_version,
+                    size,
+                    terrain.meshlet_nodes.len(),
+                    terrain.colliders.len(),
+                    terrain.chunks.len(),
+                    verify_start.elapsed()
+                );
+
+                // No steps are recorded if the processed file was already up to date
+                let steps = timings.get(source);
+                if steps.is_empty() {
+                    println!("       Already up to date");
+                }
+                for (step, duration) in steps {
+                    println!("       {0} in {1:?}", step, duration);
+                }
+            }
+            Err(err) => {
+                eprintln!("FAILED {0}: {1}", source.display(), err);
+                failures += 1;
+            }
+        }
+    }
+
+    println!(
+        "Finished in {0:?} ({1} processed, {2} failed)",
+        run.start.elapsed(),
+        run.verify_sources.len() - failures,
+        failures
+    );
+
+    exit_events.send(if failures == 0 {
+        AppExit::Success
+    } else {
+        AppExit::error()
+    });
+}
+
+//****************************************************************************
+// UTILITY
+//****************************************************************************
+
+/// Returns the paths given with `--verify`, or `None` if there are any other arguments.
+fn parse_verify_sources(mut args: impl Iterator<Item = String>) -> Option<Vec<PathBuf>> {
+    let mut verify_sources = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.strip_prefix("--verify") {
+            Some("") => verify_sources.push(PathBuf::from(args.next()?)),
+            Some(path) => verify_sources.push(PathBuf::from(path.strip_prefix('=')?)),
+            None => return None,
+        }
+    }
+
+    Some(verify_sources)
+}
+
+/// Recursively collects the terrain files in the folder, as paths relative to the assets folder.
+fn find_terrain_sources(folder: &Path, relative_folder: &Path, sources: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(folder) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let relative_path = relative_folder.join(entry.file_name());
+
+        if path.is_dir() {
+            find_terrain_sources(&path, &relative_path, sources);
+        } else if entry
+            .file_name()
+            .to_string_lossy()
+            .ends_with(&format!(".{TERRAIN_DETAILS_FILE_EXTENSION}"))
+        {
+            sources.push(relative_path);
+        }
+    }
+}
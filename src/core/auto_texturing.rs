@@ -0,0 +1,251 @@
+use super::terrain_material::*;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::{ImageSampler, ImageSamplerDescriptor};
+use serde::{Deserialize, Serialize};
+
+/// Applies a [`TerrainMaterial`] layer where the terrain's slope and height are within range.
+/// Rules are applied in order, each blending over the result of the previous rules (starting with layer 0).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct TerrainLayerRule {
+    pub layer: usize,
+    /// Slope in degrees (0 is flat).
+    pub min_slope: f32,
+    pub max_slope: f32,
+    /// Height in world units.
+    pub min_height: f32,
+    pub max_height: f32,
+    /// Width of the transition inside each end of the ranges (in degrees for slope, and world units for height).
+    /// Ends at the limits of the ranges (0° and 90° slope, and the `f32` limits for height) are open, so don't fade.
+    pub slope_falloff: f32,
+    pub height_falloff: f32,
+}
+
+impl Default for TerrainLayerRule {
+    fn default() -> Self {
+        Self {
+            layer: 0,
+            min_slope: 0.0,
+            max_slope: 90.0,
+            min_height: f32::MIN,
+            max_height: f32::MAX,
+            slope_falloff: 5.0,
+            height_falloff: 10.0,
+        }
+    }
+}
+
+impl TerrainLayerRule {
+    /// How strongly (`0.0..=1.0`) the rule applies to a vertex.
+    pub fn strength(&self, normal: Vec3, height: f32) -> f32 {
+        let slope = normal
+            .normalize_or_zero()
+            .y
+            .clamp(-1.0, 1.0)
+            .acos()
+            .to_degrees();
+
+        in_range(
+            slope,
+            (self.min_slope, self.max_slope),
+            self.slope_falloff,
+            (0.0, 90.0),
+        ) * in_range(
+            height,
+            (self.min_height, self.max_height),
+            self.height_falloff,
+            (f32::MIN, f32::MAX),
+        )
+    }
+}
+
+/// 1 within the range, fading to 0 over `falloff` inside each end of the range.
+/// Ends at (or beyond) the limits of the value are open, so e.g. a 0° minimum slope applies fully to flat ground.
+fn in_range(value: f32, (min, max): (f32, f32), falloff: f32, (lower, upper): (f32, f32)) -> f32 {
+    let falloff = falloff.max(f32::EPSILON);
+
+    let above_min = if min <= lower {
+        1.0
+    } else {
+        ((value - min) / falloff).clamp(0.0, 1.0)
+    };
+    let below_max = if max >= upper {
+        1.0
+    } else {
+        ((max - value) / falloff).clamp(0.0, 1.0)
+    };
+
+    above_min * below_max
+}
+
+/// Returns the weight of each layer for a vertex.
+pub fn terrain_layer_weights(normal: Vec3, height: f32, rules: &[TerrainLayerRule]) -> Vec4 {
+    let mut weights = Vec4::X;
+
+    for rule in rules {
+        let mut layer_weight = Vec4::ZERO;
+        layer_weight[rule.layer.min(TERRAIN_MATERIAL_LAYERS - 1)] = 1.0;
+        weights = weights.lerp(layer_weight, rule.strength(normal, height));
+    }
+
+    weights
+}
+
+/// Splat map generated from [`TerrainLayerRule`]s during processing.
+/// Meshlets can't store extra vertex attributes, so the per-vertex weights are baked into a texture instead.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct BakedSplatMap {
+    pub size: UVec2,
+    /// RGBA8 weights, in row-major order.
+    pub data: Vec<u8>,
+}
+
+impl BakedSplatMap {
+    /// Bakes the weights of a grid mesh (such as [`Heightmap::to_mesh`](super::Heightmap::to_mesh)), with one texel per vertex.
+    /// The vertices' UVs span their grid from 0 to 1, while texel centres are half a texel in from the edges,
+    /// so each texel interpolates the weights of the vertices around its centre.
+    /// Returns `None` if the mesh doesn't have `resolution` (at least 2x2) vertices with normals.
+    pub fn from_grid_mesh(
+        mesh: &Mesh,
+        resolution: UVec2,
+        rules: &[TerrainLayerRule],
+    ) -> Option<Self> {
+        let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+        let normals = mesh.attribute(Mesh::ATTRIBUTE_NORMAL)?.as_float3()?;
+
+        if resolution.cmplt(UVec2::splat(2)).any()
+            || positions.len() != (resolution.x * resolution.y) as usize
+            || normals.len() != positions.len()
+        {
+            return None;
+        }
+
+        let vertex_weights: Vec<Vec4> = positions
+            .iter()
+            .zip(normals)
+            .map(|(position, normal)| {
+                terrain_layer_weights(Vec3::from_array(*normal), position[1], rules)
+            })
+            .collect();
+
+        let vertex_weight = |x: u32, z: u32| vertex_weights[(z * resolution.x + x) as usize];
+        let last_vertex = resolution - UVec2::ONE;
+
+        let mut data = Vec::with_capacity(vertex_weights.len() * 4);
+
+        for z in 0..resolution.y {
+            for x in 0..resolution.x {
+                // Position of the texel's centre on the vertex grid
+                let grid = (UVec2::new(x, z).as_vec2() + 0.5) / resolution.as_vec2()
+                    * last_vertex.as_vec2();
+                let cell = grid.floor().as_uvec2().min(last_vertex - UVec2::ONE);
+                let t = grid - cell.as_vec2();
+
+                let weights = vertex_weight(cell.x, cell.y)
+                    .lerp(vertex_weight(cell.x + 1, cell.y), t.x)
+                    .lerp(
+                        vertex_weight(cell.x, cell.y + 1)
+                            .lerp(vertex_weight(cell.x + 1, cell.y + 1), t.x),
+                        t.y,
+                    );
+
+                data.extend(
+                    (weights * u8::MAX as f32)
+                        .round()
+                        .to_array()
+                        .map(|weight| weight as u8),
+                );
+            }
+        }
+
+        Some(Self {
+            size: resolution,
+            data,
+        })
+    }
+
+    pub fn to_image(&self) -> Image {
+        let mut image = Image::new(
+            Extent3d {
+                width: self.size.x,
+                height: self.size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            self.data.clone(),
+            TextureFormat::Rgba8Unorm,
+            RenderAssetUsages::RENDER_WORLD,
+        );
+        image.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor::linear());
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rules_blend_layers_by_slope_and_height() {
+        let rules = [
+            // Rock on steep slopes
+            TerrainLayerRule {
+                layer: 1,
+                min_slope: 35.0,
+                ..default()
+            },
+            // Snow up high
+            TerrainLayerRule {
+                layer: 2,
+                min_height: 200.0,
+                ..default()
+            },
+        ];
+
+        let steep = Vec3::new(1.0, 1.0, 0.0);
+
+        assert_eq!(terrain_layer_weights(Vec3::Y, 0.0, &rules), Vec4::X);
+        assert_eq!(terrain_layer_weights(steep, 0.0, &rules), Vec4::Y);
+        assert_eq!(terrain_layer_weights(steep, 300.0, &rules), Vec4::Z);
+        // Flat ground is fully covered, as the default slope range is open
+        assert_eq!(terrain_layer_weights(Vec3::Y, 300.0, &rules), Vec4::Z);
+
+        // Transitions start at the end of the range
+        assert_eq!(terrain_layer_weights(Vec3::Y, 200.0, &rules), Vec4::X);
+
+        // Halfway through the height transition
+        let weights = terrain_layer_weights(Vec3::Y, 205.0, &rules);
+        assert!(weights.abs_diff_eq(Vec4::new(0.5, 0.0, 0.5, 0.0), 1e-5));
+    }
+
+    #[test]
+    fn splat_map_is_baked_at_texel_centres() {
+        let rules = [TerrainLayerRule {
+            layer: 1,
+            min_height: 1.0,
+            height_falloff: 1.0,
+            ..default()
+        }];
+
+        // 2x2 grid with the right column raised, so it's fully layer 1
+        let positions = vec![
+            [0.0, 0.0, 0.0],
+            [1.0, 2.0, 0.0],
+            [0.0, 0.0, 1.0],
+            [1.0, 2.0, 1.0],
+        ];
+        let mesh = Mesh::new(
+            bevy::render::mesh::PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 1.0, 0.0]; 4])
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+
+        let splat_map = BakedSplatMap::from_grid_mesh(&mesh, UVec2::splat(2), &rules).unwrap();
+
+        // The texel centres are a quarter of the way in from each vertex column
+        assert_eq!(&splat_map.data[0..8], &[191, 64, 0, 0, 64, 191, 0, 0]);
+    }
+}
@@ -0,0 +1,167 @@
+use super::colliders::*;
+use super::meshlet_scene::*;
+use super::serialised_meshlet_scene::*;
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, VertexAttributeValues};
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct SerialisedTerrainChunk {
+    /// Grid coordinate of the chunk on the XZ plane (i.e. the chunk covers `coord * chunk_size` to `(coord + 1) * chunk_size`).
+    pub coord: IVec2,
+    /// World-space bounds of the chunk's geometry.
+    pub min: Vec3,
+    pub max: Vec3,
+    pub meshlet_node: SerialisedMeshletNode,
+    pub collider_node: SerialisedTerrainColliderNode,
+}
+
+/// A spatial chunk of the terrain, loaded as a `Chunk{x}_{z}` sub-asset of the terrain details.
+#[derive(Asset, TypePath)]
+pub struct TerrainChunk {
+    pub coord: IVec2,
+    pub min: Vec3,
+    pub max: Vec3,
+    pub meshlet_node: MeshletNode,
+    pub collider_node: TerrainColliderNode,
+}
+
+impl TerrainChunk {
+    /// Returns the sub-asset label for the chunk at the given coordinate.
+    pub fn label(coord: IVec2) -> String {
+        format!("Chunk{0}_{1}", coord.x, coord.y)
+    }
+
+    /// Spawns the meshlets & colliders of this chunk underneath the parent.
+    /// Returns the chunk's entity.
+    pub fn spawn(&self, parent: &mut ChildBuilder) -> Entity {
+        parent
+            .spawn((
+                Name::from(Self::label(self.coord)),
+                SpatialBundle::default(),
+            ))
+            .with_children(|chunk_parent| {
+                self.meshlet_node.spawn(chunk_parent);
+                self.collider_node.spawn(chunk_parent);
+            })
+            .id()
+    }
+}
+
+/// Splits a mesh into a grid of square chunks on the XZ plane, assigning each triangle to the chunk containing its centroid.
+/// Returns the sub-meshes (containing only the vertices they use), sorted by chunk coordinate.
+pub fn split_mesh_into_chunks(mesh: &Mesh, chunk_size: f32) -> Vec<(IVec2, Mesh)> {
+    let Some(positions) = mesh
+        .attribute(Mesh::ATTRIBUTE_POSITION)
+        .and_then(|positions| positions.as_float3())
+    else {
+        return Vec::new();
+    };
+
+    let indices: Vec<u32> = match mesh.indices() {
+        Some(indices) => indices.iter().map(|index| index as u32).collect(),
+        None => (0..positions.len() as u32).collect(),
+    };
+
+    let mut chunk_indices: HashMap<IVec2, Vec<u32>> = HashMap::new();
+
+    for triangle in indices.chunks_exact(3) {
+        let centroid = triangle
+            .iter()
+            .map(|index| Vec3::from_array(positions[*index as usize]))
+            .sum::<Vec3>()
+            / 3.0;
+
+        let coord = (centroid.xz() / chunk_size).floor().as_ivec2();
+        chunk_indices
+            .entry(coord)
+            .or_default()
+            .extend_from_slice(triangle);
+    }
+
+    let mut chunks: Vec<(IVec2, Mesh)> = chunk_indices
+        .into_iter()
+        .map(|(coord, indices)| (coord, extract_sub_mesh(mesh, &indices)))
+        .collect();
+
+    chunks.sort_by_key(|(coord, _)| (coord.y, coord.x));
+    chunks
+}
+
+/// Copies the given triangles into a new mesh, keeping only the vertices they reference.
+fn extract_sub_mesh(mesh: &Mesh, indices: &[u32]) -> Mesh {
+    let mut remapped_indices: HashMap<u32, u32> = HashMap::new();
+    let mut vertices: Vec<usize> = Vec::new();
+
+    let indices: Vec<u32> = indices
+        .iter()
+        .map(|index| {
+            *remapped_indices.entry(*index).or_insert_with(|| {
+                vertices.push(*index as usize);
+                (vertices.len() - 1) as u32
+            })
+        })
+        .collect();
+
+    let mut sub_mesh = mesh.clone();
+
+    for (_, values) in sub_mesh.attributes_mut() {
+        *values = gather_vertices(values, &vertices);
+    }
+
+    sub_mesh.insert_indices(Indices::U32(indices));
+    sub_mesh
+}
+
+macro_rules! gather_vertex_attribute_values {
+    ($values:expr, $vertices:expr, $($variant:ident),*) => {
+        match $values {
+            $(VertexAttributeValues::$variant(values) => VertexAttributeValues::$variant(
+                $vertices.iter().map(|vertex| values[*vertex]).collect(),
+            ),)*
+        }
+    };
+}
+
+fn gather_vertices(values: &VertexAttributeValues, vertices: &[usize]) -> VertexAttributeValues {
+    gather_vertex_attribute_values!(
+        values, vertices, Float32, Sint32, Uint32, Float32x2, Sint32x2, Uint32x2, Float32x3,
+        Sint32x3, Uint32x3, Float32x4, Sint32x4, Uint32x4, Sint16x2, Snorm16x2, Uint16x2,
+        Unorm16x2, Sint16x4, Snorm16x4, Uint16x4, Unorm16x4, Sint8x2, Snorm8x2, Uint8x2, Unorm8x2,
+        Sint8x4, Snorm8x4, Uint8x4, Unorm8x4
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_plane_into_chunks() {
+        // 4x4 quads spanning -2..2 on X & Z
+        let mesh = Plane3d::default()
+            .mesh()
+            .size(4.0, 4.0)
+            .subdivisions(3)
+            .build();
+
+        let chunks = split_mesh_into_chunks(&mesh, 2.0);
+
+        assert_eq!(
+            chunks.iter().map(|(coord, _)| *coord).collect::<Vec<_>>(),
+            vec![
+                IVec2::new(-1, -1),
+                IVec2::new(0, -1),
+                IVec2::new(-1, 0),
+                IVec2::new(0, 0)
+            ]
+        );
+
+        for (_, chunk_mesh) in &chunks {
+            // Each chunk has 2x2 quads, using a 3x3 grid of vertices
+            assert_eq!(chunk_mesh.indices().unwrap().len(), 2 * 2 * 6);
+            assert_eq!(chunk_mesh.count_vertices(), 9);
+        }
+    }
+}
@@ -2,30 +2,323 @@ use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use serde::{Deserialize, Serialize};
 
+/// The shape of the colliders generated for each mesh primitive.
+/// Aliases allow lowercase names in gLTF extras (e.g. `"collider": "convex_hull"` as a Blender custom property).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum TerrainColliderShape {
+    #[default]
+    #[serde(alias = "trimesh")]
+    TriMesh,
+    #[serde(alias = "convex_hull")]
+    ConvexHull,
+    /// V-HACD convex decomposition, for dynamic bodies to collide with concave props.
+    #[serde(alias = "convex_decomposition", alias = "vhacd")]
+    ConvexDecomposition,
+    /// Uses a heightfield if the mesh is a regular XZ grid, otherwise falls back to a TriMesh.
+    #[serde(alias = "heightfield")]
+    Heightfield,
+    /// Box fitted to the mesh's bounding box.
+    #[serde(alias = "box", alias = "cuboid")]
+    Cuboid,
+    /// Sphere around the centre of the mesh's bounding box, with a radius of its largest half extent.
+    #[serde(alias = "sphere", alias = "ball")]
+    Ball,
+    /// Capsule along the longest axis of the mesh's bounding box.
+    #[serde(alias = "capsule")]
+    Capsule,
+    /// No collider.
+    #[serde(alias = "none")]
+    None,
+}
+
+impl TerrainColliderShape {
+    /// Returns `None` for shapes which aren't generated from the mesh's triangles.
+    pub fn to_computed_shape(self) -> Option<ComputedColliderShape> {
+        match self {
+            TerrainColliderShape::TriMesh | TerrainColliderShape::Heightfield => {
+                Some(ComputedColliderShape::TriMesh)
+            }
+            TerrainColliderShape::ConvexHull => Some(ComputedColliderShape::ConvexHull),
+            TerrainColliderShape::ConvexDecomposition => Some(
+                ComputedColliderShape::ConvexDecomposition(VHACDParameters::default()),
+            ),
+            TerrainColliderShape::Cuboid
+            | TerrainColliderShape::Ball
+            | TerrainColliderShape::Capsule
+            | TerrainColliderShape::None => None,
+        }
+    }
+}
+
+/// Friction & restitution of a node's colliders.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(default)]
+pub struct TerrainPhysicsMaterial {
+    pub friction: f32,
+    pub restitution: f32,
+}
+
+impl Default for TerrainPhysicsMaterial {
+    /// Rapier's defaults.
+    fn default() -> Self {
+        Self {
+            friction: 0.5,
+            restitution: 0.0,
+        }
+    }
+}
+
+/// Collider node as stored in the processed terrain, with colliders referring to a shared table of shapes.
 #[derive(Serialize, Deserialize)]
+pub struct SerialisedTerrainColliderNode {
+    pub colliders: Vec<SerialisedTerrainCollider>,
+    /// See [`TerrainColliderNode::collider_shape`].
+    pub collider_shape: TerrainColliderShape,
+    pub physics_material: Option<TerrainPhysicsMaterial>,
+    pub collision_group: Option<u32>,
+    pub transform: Transform,
+    pub children: Vec<SerialisedTerrainColliderNode>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SerialisedTerrainCollider {
+    /// Index into the terrain's collider shapes (shared by every node using the same gLTF mesh).
+    pub shape_index: usize,
+    /// See [`TerrainCollider::transform`].
+    pub transform: Transform,
+}
+
 pub struct TerrainColliderNode {
-    pub colliders: Vec<Collider>,
+    pub colliders: Vec<TerrainCollider>,
+    /// The shape chosen for the node (by the processing settings, or overridden per node).
+    /// Chunks use the default shape, but the meshes within them may have been overridden.
+    pub collider_shape: TerrainColliderShape,
+    /// Uses Rapier's default friction & restitution if not set.
+    pub physics_material: Option<TerrainPhysicsMaterial>,
+    /// Collision group (`0..32`) the colliders are a member of (they still collide with every group).
+    /// Uses the default groups if not set.
+    pub collision_group: Option<u32>,
     pub transform: Transform,
     pub children: Vec<TerrainColliderNode>,
 }
 
 impl TerrainColliderNode {
+    /// Looks up the colliders of the node and its children.
+    /// Cloning a Collider reuses its SharedShape, so nodes referring to the same shape share it.
+    /// The shape indices must have been validated (see `ProcessedTerrainDetails::validate_collider_shape_indices`).
+    pub fn load(node: SerialisedTerrainColliderNode, collider_shapes: &[Collider]) -> Self {
+        Self {
+            colliders: node
+                .colliders
+                .into_iter()
+                .map(|collider| TerrainCollider {
+                    collider: collider_shapes[collider.shape_index].clone(),
+                    transform: collider.transform,
+                })
+                .collect(),
+            collider_shape: node.collider_shape,
+            physics_material: node.physics_material,
+            collision_group: node.collision_group,
+            transform: node.transform,
+            children: node
+                .children
+                .into_iter()
+                .map(|child| TerrainColliderNode::load(child, collider_shapes))
+                .collect(),
+        }
+    }
+
+    /// Spawns an entity with this node's transform, with the colliders and child nodes underneath it.
     pub fn spawn(&self, parent: &mut ChildBuilder) {
-        for collider in &self.colliders {
-            parent.spawn((
-                TransformBundle {
-                    local: self.transform,
-                    ..default()
-                },
-                collider.clone(),
-                RigidBody::Fixed,
-            ));
+        parent
+            .spawn(TransformBundle::from_transform(self.transform))
+            .with_children(|node_parent| {
+                for collider in &self.colliders {
+                    let mut collider_entity = node_parent.spawn((
+                        TransformBundle::from_transform(collider.transform),
+                        collider.collider.clone(),
+                        RigidBody::Fixed,
+                    ));
+
+                    if let Some(physics_material) = self.physics_material {
+                        collider_entity.insert((
+                            Friction::coefficient(physics_material.friction),
+                            Restitution::coefficient(physics_material.restitution),
+                        ));
+                    }
+
+                    if let Some(collision_group) = self.collision_group {
+                        collider_entity.insert(CollisionGroups::new(
+                            Group::from_bits_truncate(1 << collision_group),
+                            Group::ALL,
+                        ));
+                    }
+                }
+
+                for child in &self.children {
+                    child.spawn(node_parent)
+                }
+            });
+    }
+}
+
+#[derive(Clone)]
+pub struct TerrainCollider {
+    pub collider: Collider,
+    /// Offset from the node (e.g. heightfields are centred on their origin, unlike the mesh they're generated from).
+    pub transform: Transform,
+}
+
+impl TerrainCollider {
+    /// Adds the collider's shape to the table, and returns the serialised collider referring to it.
+    pub fn into_serialised(self, collider_shapes: &mut Vec<Collider>) -> SerialisedTerrainCollider {
+        collider_shapes.push(self.collider);
+
+        SerialisedTerrainCollider {
+            shape_index: collider_shapes.len() - 1,
+            transform: self.transform,
         }
+    }
+}
+
+impl TerrainCollider {
+    /// Fits a primitive shape (cuboid, ball or capsule) to the mesh's bounding box.
+    /// Returns `None` for other shapes, or if the mesh doesn't have positions.
+    pub fn fit_primitive(mesh: &Mesh, collider_shape: TerrainColliderShape) -> Option<Self> {
+        let aabb = mesh.compute_aabb()?;
+        let half_extents = Vec3::from(aabb.half_extents);
 
-        parent.spawn(()).with_children(|inner_parent| {
-            for child in &self.children {
-                child.spawn(inner_parent)
+        let collider = match collider_shape {
+            TerrainColliderShape::Cuboid => {
+                Collider::cuboid(half_extents.x, half_extents.y, half_extents.z)
             }
-        });
+            TerrainColliderShape::Ball => Collider::ball(half_extents.max_element()),
+            TerrainColliderShape::Capsule => {
+                // The radius covers the two shorter axes, and the segment the rest of the longest
+                let axis = Vec3::AXES
+                    .into_iter()
+                    .max_by(|a, b| a.dot(half_extents).total_cmp(&b.dot(half_extents)))
+                    .unwrap();
+                let radius = (half_extents * (Vec3::ONE - axis)).max_element();
+                let half_height = (half_extents.dot(axis) - radius).max(0.0);
+
+                Collider::capsule(-axis * half_height, axis * half_height, radius)
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            collider,
+            transform: Transform::from_translation(aabb.center.into()),
+        })
+    }
+}
+
+impl From<Collider> for TerrainCollider {
+    fn from(collider: Collider) -> Self {
+        Self {
+            collider,
+            transform: Transform::IDENTITY,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_4;
+
+    /// Each node uses a ball collider with a unique radius so it can be identified after spawning.
+    fn test_node(
+        radius: f32,
+        transform: Transform,
+        children: Vec<TerrainColliderNode>,
+    ) -> TerrainColliderNode {
+        TerrainColliderNode {
+            colliders: vec![Collider::ball(radius).into()],
+            collider_shape: TerrainColliderShape::Ball,
+            physics_material: None,
+            collision_group: None,
+            transform,
+            children,
+        }
+    }
+
+    #[test]
+    fn colliders_inherit_node_hierarchy_transforms() {
+        // Mirrors a gLTF hierarchy of Root -> Child -> Grandchild
+        let root_transform = Transform::from_xyz(0.0, -2.0, 8.0);
+        let child_transform = Transform::from_xyz(3.0, 0.0, 0.0)
+            .with_rotation(Quat::from_rotation_x(FRAC_PI_4))
+            .with_scale(Vec3::new(1.0, 0.5, 1.0));
+        let grandchild_transform = Transform::from_xyz(0.0, 4.0, 1.0);
+
+        let node = test_node(
+            1.0,
+            root_transform,
+            vec![test_node(
+                2.0,
+                child_transform,
+                vec![test_node(3.0, grandchild_transform, Vec::new())],
+            )],
+        );
+
+        let mut app = App::new();
+        app.add_plugins((TransformPlugin, HierarchyPlugin));
+        app.world_mut()
+            .commands()
+            .spawn(TransformBundle::default())
+            .with_children(|parent| node.spawn(parent));
+        app.world_mut().flush();
+        app.update();
+
+        let root = GlobalTransform::from(root_transform);
+        let child = root.mul_transform(child_transform);
+        let expected = [
+            (1.0, root),
+            (2.0, child),
+            (3.0, child.mul_transform(grandchild_transform)),
+        ];
+
+        let mut query = app.world_mut().query::<(&Collider, &GlobalTransform)>();
+        let world = app.world();
+        assert_eq!(query.iter(world).count(), expected.len());
+
+        for (radius, expected_transform) in expected {
+            let (_, global_transform) = query
+                .iter(world)
+                .find(|(collider, _)| collider.raw.as_ball().unwrap().radius == radius)
+                .unwrap();
+
+            assert!(global_transform
+                .affine()
+                .abs_diff_eq(expected_transform.affine(), 1e-5));
+        }
+    }
+
+    #[test]
+    fn primitive_colliders_fit_mesh_bounds() {
+        let mesh = Cuboid::new(2.0, 4.0, 6.0)
+            .mesh()
+            .build()
+            .translated_by(Vec3::new(1.0, 2.0, 3.0));
+
+        let cuboid = TerrainCollider::fit_primitive(&mesh, TerrainColliderShape::Cuboid).unwrap();
+        assert_eq!(cuboid.transform.translation, Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(
+            cuboid.collider.as_cuboid().unwrap().half_extents(),
+            Vec3::new(1.0, 2.0, 3.0)
+        );
+
+        let ball = TerrainCollider::fit_primitive(&mesh, TerrainColliderShape::Ball).unwrap();
+        assert_eq!(ball.collider.as_ball().unwrap().radius(), 3.0);
+
+        // Along Z, with the radius covering Y
+        let capsule = TerrainCollider::fit_primitive(&mesh, TerrainColliderShape::Capsule).unwrap();
+        let capsule = capsule.collider.as_capsule().unwrap();
+        assert_eq!(capsule.radius(), 2.0);
+        assert_eq!(capsule.segment().b(), Vec3::new(0.0, 0.0, 1.0));
+
+        assert!(TerrainCollider::fit_primitive(&mesh, TerrainColliderShape::TriMesh).is_none());
     }
 }
@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use meshopt::{simplify, typed_to_bytes, SimplifyOptions, VertexDataAdapter};
+use serde::{Deserialize, Serialize};
+
+/// Node name prefix (Unreal/Blender convention) marking a convex collision mesh, e.g. `UCX_Rock` or `UCX_Rock_01`.
+/// Collision meshes use a convex hull collider unless overridden by extras or `collider_overrides`.
+pub const COLLISION_PROXY_PREFIX: &str = "UCX_";
+/// Node name suffix marking a collision mesh, e.g. `Rock_col`.
+pub const COLLISION_PROXY_SUFFIX: &str = "_col";
+
+/// Returns the name of the render node a collision node replaces the collider of.
+/// Returns `None` if the node isn't a collision node.
+pub fn collision_proxy_target(node_name: &str) -> Option<&str> {
+    if let Some(name) = node_name.strip_prefix(COLLISION_PROXY_PREFIX) {
+        // Numbered suffixes allow several collision meshes for the same node
+        let name = match name.rsplit_once('_') {
+            Some((target, number))
+                if !number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) =>
+            {
+                target
+            }
+            _ => name,
+        };
+
+        return Some(name);
+    }
+
+    node_name.strip_suffix(COLLISION_PROXY_SUFFIX)
+}
+
+/// Reduces the number of triangles used to generate colliders (the meshlets still use the full mesh).
+/// The targets are applied to each mesh primitive separately.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ColliderSimplification {
+    /// Fraction (`0.0..=1.0`) of each mesh's triangles to keep.
+    /// Scales with the mesh, so small props aren't reduced to a handful of triangles.
+    pub target_ratio: f32,
+    /// Maximum number of triangles to keep for each mesh, if lower than the `target_ratio` target.
+    /// Caps the budget of large meshes (e.g. the terrain itself).
+    pub target_triangles: Option<usize>,
+    /// Maximum deviation from the original mesh, relative to the mesh's size (e.g. `0.01` is 1%).
+    pub max_error: f32,
+}
+
+impl Default for ColliderSimplification {
+    fn default() -> Self {
+        Self {
+            target_ratio: 0.25,
+            target_triangles: None,
+            max_error: 0.01,
+        }
+    }
+}
+
+/// Returns a simplified copy of the mesh with only positions & indices (which is all colliders need).
+/// Borders are kept in place, so chunks and neighbouring meshes still line up.
+/// Returns `None` if the mesh doesn't have indexed positions, or couldn't be simplified.
+pub fn simplify_collision_mesh(
+    mesh: &Mesh,
+    simplification: &ColliderSimplification,
+) -> Option<Mesh> {
+    let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+    let indices: Vec<u32> = mesh.indices()?.iter().map(|index| index as u32).collect();
+
+    let vertices = VertexDataAdapter::new(
+        typed_to_bytes(positions),
+        std::mem::size_of::<[f32; 3]>(),
+        0,
+    )
+    .ok()?;
+
+    let mut target_triangles =
+        ((indices.len() / 3) as f32 * simplification.target_ratio.clamp(0.0, 1.0)) as usize;
+    if let Some(max_triangles) = simplification.target_triangles {
+        target_triangles = target_triangles.min(max_triangles);
+    }
+
+    let simplified_indices = simplify(
+        &indices,
+        &vertices,
+        target_triangles * 3,
+        simplification.max_error,
+        SimplifyOptions::LockBorder,
+        None,
+    );
+
+    if simplified_indices.is_empty() || simplified_indices.len() >= indices.len() {
+        return None;
+    }
+
+    let (simplified_indices, simplified_positions) =
+        compact_vertices(&simplified_indices, positions);
+
+    Some(
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_indices(Indices::U32(simplified_indices))
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, simplified_positions),
+    )
+}
+
+/// Drops the vertices which aren't referenced by any index, and remaps the indices to the remaining vertices.
+fn compact_vertices(indices: &[u32], positions: &[[f32; 3]]) -> (Vec<u32>, Vec<[f32; 3]>) {
+    let mut remap = vec![u32::MAX; positions.len()];
+    let mut compacted_positions = Vec::new();
+
+    let compacted_indices = indices
+        .iter()
+        .map(|&index| {
+            let new_index = &mut remap[index as usize];
+            if *new_index == u32::MAX {
+                *new_index = compacted_positions.len() as u32;
+                compacted_positions.push(positions[index as usize]);
+            }
+            *new_index
+        })
+        .collect();
+
+    (compacted_indices, compacted_positions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_collision_proxy_targets() {
+        assert_eq!(collision_proxy_target("UCX_Rock"), Some("Rock"));
+        assert_eq!(collision_proxy_target("UCX_Rock_01"), Some("Rock"));
+        assert_eq!(collision_proxy_target("UCX_Big_Rock"), Some("Big_Rock"));
+        assert_eq!(collision_proxy_target("Rock_col"), Some("Rock"));
+        assert_eq!(collision_proxy_target("Rock"), None);
+        assert_eq!(collision_proxy_target("Rock_01"), None);
+    }
+
+    #[test]
+    fn simplifies_flat_grid() {
+        let mesh = Plane3d::default()
+            .mesh()
+            .size(8.0, 8.0)
+            .subdivisions(7)
+            .build();
+        let triangles = mesh.indices().unwrap().len() / 3;
+        let vertices = mesh.count_vertices();
+
+        let simplified =
+            simplify_collision_mesh(&mesh, &ColliderSimplification::default()).unwrap();
+
+        assert!(simplified.indices().unwrap().len() / 3 < triangles);
+        assert!(simplified.count_vertices() < vertices);
+        assert!(simplified
+            .indices()
+            .unwrap()
+            .iter()
+            .all(|index| index < simplified.count_vertices()));
+        assert!(!simplified.contains_attribute(Mesh::ATTRIBUTE_NORMAL));
+    }
+
+    #[test]
+    fn caps_triangle_count() {
+        // 16x16 quads (512 triangles), with few enough border vertices to get below the cap
+        let mesh = Plane3d::default()
+            .mesh()
+            .size(8.0, 8.0)
+            .subdivisions(15)
+            .build();
+        let triangle_count = |mesh: &Mesh| mesh.indices().unwrap().len() / 3;
+
+        let mut simplification = ColliderSimplification {
+            target_ratio: 0.5,
+            target_triangles: None,
+            max_error: 1.0,
+        };
+
+        // The ratio alone keeps more triangles than the cap...
+        let ratio_simplified = simplify_collision_mesh(&mesh, &simplification).unwrap();
+        assert!(triangle_count(&ratio_simplified) > 128);
+
+        // ...so the cap wins
+        simplification.target_triangles = Some(128);
+        let capped_simplified = simplify_collision_mesh(&mesh, &simplification).unwrap();
+        assert!(triangle_count(&capped_simplified) <= 128);
+    }
+}
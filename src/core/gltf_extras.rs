@@ -0,0 +1,86 @@
+use super::colliders::*;
+use bevy::gltf::GltfExtras;
+use bevy::prelude::*;
+use serde::Deserialize;
+
+/// Processing overrides read from the extras of a gLTF node or mesh (e.g. Blender custom properties).
+/// Unknown keys are ignored, so other custom properties can be exported alongside them.
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct TerrainGltfExtras {
+    /// Overrides the collider shape for the node's meshes.
+    pub collider: Option<TerrainColliderShape>,
+    /// Only uses the node for physics (like collision nodes).
+    pub no_meshlet: Option<bool>,
+    /// Name of one of the terrain's physics materials, applied to the node's colliders.
+    pub physics_material: Option<String>,
+    /// Collision group (`0..32`) of the node's colliders, which still collide with every group.
+    pub collision_group: Option<u32>,
+    /// When chunking, places the node's meshes in this chunk as a whole, instead of splitting them.
+    pub streaming_chunk: Option<IVec2>,
+}
+
+impl TerrainGltfExtras {
+    /// Parses the extras' JSON (returning the defaults if there aren't any extras).
+    pub fn from_gltf_extras(extras: Option<&GltfExtras>) -> Result<Self, serde_json::Error> {
+        match extras {
+            Some(extras) => serde_json::from_str(&extras.value),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Uses the values from `fallback` for keys which aren't set (e.g. a node's mesh extras).
+    pub fn or(self, fallback: &Self) -> Self {
+        Self {
+            collider: self.collider.or(fallback.collider),
+            no_meshlet: self.no_meshlet.or(fallback.no_meshlet),
+            physics_material: self
+                .physics_material
+                .or_else(|| fallback.physics_material.clone()),
+            collision_group: self.collision_group.or(fallback.collision_group),
+            streaming_chunk: self.streaming_chunk.or(fallback.streaming_chunk),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_collider_overrides() {
+        let extras = GltfExtras {
+            value: r#"{"collider": "vhacd", "prop": 1}"#.to_string(),
+        };
+        let extras = TerrainGltfExtras::from_gltf_extras(Some(&extras)).unwrap();
+        assert_eq!(
+            extras.collider,
+            Some(TerrainColliderShape::ConvexDecomposition)
+        );
+
+        let extras = GltfExtras {
+            value: r#"{"collider": "teapot"}"#.to_string(),
+        };
+        assert!(TerrainGltfExtras::from_gltf_extras(Some(&extras)).is_err());
+    }
+
+    #[test]
+    fn node_extras_override_mesh_extras() {
+        let node_extras = GltfExtras {
+            value: r#"{"no_meshlet": false, "streaming_chunk": [2, -1]}"#.to_string(),
+        };
+        let mesh_extras = GltfExtras {
+            value: r#"{"no_meshlet": true, "physics_material": "Ice", "collision_group": 3}"#
+                .to_string(),
+        };
+
+        let extras = TerrainGltfExtras::from_gltf_extras(Some(&node_extras))
+            .unwrap()
+            .or(&TerrainGltfExtras::from_gltf_extras(Some(&mesh_extras)).unwrap());
+
+        assert_eq!(extras.no_meshlet, Some(false));
+        assert_eq!(extras.physics_material.as_deref(), Some("Ice"));
+        assert_eq!(extras.collision_group, Some(3));
+        assert_eq!(extras.streaming_chunk, Some(IVec2::new(2, -1)));
+    }
+}
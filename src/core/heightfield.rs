@@ -0,0 +1,171 @@
+use super::colliders::*;
+use bevy::prelude::*;
+use bevy::render::mesh::Indices;
+use bevy_rapier3d::prelude::*;
+
+/// A regular XZ grid of heights, which can be used for a heightfield collider.
+pub struct HeightfieldGrid {
+    /// Heights in column-major order (rows run along Z, columns along X).
+    pub heights: Vec<f32>,
+    pub num_rows: usize,
+    pub num_cols: usize,
+    /// The size of the grid along X (x) and Z (y).
+    pub size: Vec2,
+    /// The centre of the grid on the XZ plane (heightfield colliders are centred on their origin).
+    pub center: Vec2,
+}
+
+impl HeightfieldGrid {
+    /// Detects whether the mesh is a regular XZ grid with one height per grid point.
+    /// Returns `None` if it isn't (e.g. overhangs, holes or irregular spacing).
+    pub fn from_mesh(mesh: &Mesh) -> Option<Self> {
+        let positions = mesh.attribute(Mesh::ATTRIBUTE_POSITION)?.as_float3()?;
+
+        let (min, max) = positions.iter().fold(
+            (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+            |(min, max), position| {
+                let position = Vec3::from_array(*position);
+                (min.min(position), max.max(position))
+            },
+        );
+
+        let size = Vec2::new(max.x - min.x, max.z - min.z);
+        let tolerance = size.max_element() * 1e-4;
+
+        let num_cols = count_distinct(positions.iter().map(|position| position[0]), tolerance);
+        let num_rows = count_distinct(positions.iter().map(|position| position[2]), tolerance);
+
+        if num_rows < 2 || num_cols < 2 {
+            return None;
+        }
+
+        // Every grid point needs a vertex, which also rules out irregular meshes before allocating the grid
+        // (their distinct X & Z values would otherwise make a grid of roughly the square of their vertex count)
+        if num_rows
+            .checked_mul(num_cols)
+            .map_or(true, |grid_points| grid_points > positions.len())
+        {
+            return None;
+        }
+
+        let cell_size = Vec2::new(
+            size.x / (num_cols - 1) as f32,
+            size.y / (num_rows - 1) as f32,
+        );
+
+        // Vertices may be duplicated (e.g. UV seams), but must agree on their height
+        let mut heights: Vec<Option<f32>> = vec![None; num_rows * num_cols];
+
+        for position in positions {
+            let col = ((position[0] - min.x) / cell_size.x).round();
+            let row = ((position[2] - min.z) / cell_size.y).round();
+
+            if (min.x + col * cell_size.x - position[0]).abs() > tolerance
+                || (min.z + row * cell_size.y - position[2]).abs() > tolerance
+            {
+                return None;
+            }
+
+            let height = &mut heights[col as usize * num_rows + row as usize];
+
+            match height {
+                Some(existing) if (*existing - position[1]).abs() > tolerance => return None,
+                _ => *height = Some(position[1]),
+            }
+        }
+
+        // Every cell must be covered by two triangles (i.e. no holes)
+        let expected_triangles = (num_rows - 1) * (num_cols - 1) * 2;
+        let triangles = match mesh.indices() {
+            Some(Indices::U16(indices)) => indices.len() / 3,
+            Some(Indices::U32(indices)) => indices.len() / 3,
+            None => positions.len() / 3,
+        };
+
+        if triangles != expected_triangles {
+            return None;
+        }
+
+        Some(Self {
+            heights: heights.into_iter().collect::<Option<Vec<f32>>>()?,
+            num_rows,
+            num_cols,
+            size,
+            center: Vec2::new(min.x + max.x, min.z + max.z) * 0.5,
+        })
+    }
+
+    pub fn to_collider(&self) -> TerrainCollider {
+        TerrainCollider {
+            collider: Collider::heightfield(
+                self.heights.clone(),
+                self.num_rows,
+                self.num_cols,
+                Vec3::new(self.size.x, 1.0, self.size.y),
+            ),
+            transform: Transform::from_xyz(self.center.x, 0.0, self.center.y),
+        }
+    }
+}
+
+/// Counts the number of distinct values (values within the tolerance of each other are treated as equal).
+fn count_distinct(values: impl Iterator<Item = f32>, tolerance: f32) -> usize {
+    let mut values: Vec<f32> = values.collect();
+    values.sort_by(f32::total_cmp);
+    values.dedup_by(|a, b| (*a - *b).abs() <= tolerance);
+    values.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::render::mesh::VertexAttributeValues;
+
+    #[test]
+    fn detects_subdivided_plane() {
+        let mesh = Plane3d::default()
+            .mesh()
+            .size(20.0, 10.0)
+            .subdivisions(3)
+            .build()
+            .translated_by(Vec3::new(5.0, 2.0, -5.0));
+
+        let grid = HeightfieldGrid::from_mesh(&mesh).unwrap();
+
+        assert_eq!(grid.num_rows, 5);
+        assert_eq!(grid.num_cols, 5);
+        assert!(grid.size.abs_diff_eq(Vec2::new(20.0, 10.0), 1e-5));
+        assert!(grid.center.abs_diff_eq(Vec2::new(5.0, -5.0), 1e-5));
+        assert!(grid
+            .heights
+            .iter()
+            .all(|height| (height - 2.0).abs() < 1e-5));
+    }
+
+    #[test]
+    fn rejects_non_grid_mesh() {
+        let mesh = Sphere::new(1.0).mesh().build();
+
+        assert!(HeightfieldGrid::from_mesh(&mesh).is_none());
+    }
+    #[test]
+    fn rejects_jittered_grid() {
+        let mut mesh = Plane3d::default()
+            .mesh()
+            .size(10.0, 10.0)
+            .subdivisions(15)
+            .build();
+
+        // Every vertex gets a distinct X & Z value, so the grid would be vertex count squared
+        if let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)
+        {
+            for (i, position) in positions.iter_mut().enumerate() {
+                position[0] += i as f32 * 1e-2;
+                position[2] += i as f32 * 2e-2;
+            }
+        }
+
+        assert!(HeightfieldGrid::from_mesh(&mesh).is_none());
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::prelude::*;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::TextureFormat;
+
+/// Greyscale height data, used to generate terrain meshes.
+pub struct Heightmap {
+    pub size: UVec2,
+    /// Heights normalised to `0.0..=1.0`, in row-major order (rows run along Z).
+    pub heights: Vec<f32>,
+}
+
+impl Heightmap {
+    /// Reads the first channel of an 8 or 16-bit image.
+    /// Returns `None` for unsupported texture formats.
+    pub fn from_image(image: &Image) -> Option<Self> {
+        let heights = match image.texture_descriptor.format {
+            TextureFormat::R16Uint | TextureFormat::R16Unorm => image
+                .data
+                .chunks_exact(2)
+                .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]) as f32 / u16::MAX as f32)
+                .collect(),
+            TextureFormat::R8Unorm => image
+                .data
+                .iter()
+                .map(|value| *value as f32 / u8::MAX as f32)
+                .collect(),
+            TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => image
+                .data
+                .chunks_exact(4)
+                .map(|pixel| pixel[0] as f32 / u8::MAX as f32)
+                .collect(),
+            _ => return None,
+        };
+
+        Self::new(image.size(), heights)
+    }
+
+    /// Reads a raw little-endian 16-bit heightmap.
+    /// Returns `None` if the number of bytes doesn't match the size.
+    pub fn from_raw_r16(bytes: &[u8], size: UVec2) -> Option<Self> {
+        if bytes.len() != (size.x * size.y * 2) as usize {
+            return None;
+        }
+
+        let heights = bytes
+            .chunks_exact(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as f32 / u16::MAX as f32)
+            .collect();
+
+        Self::new(size, heights)
+    }
+
+    fn new(size: UVec2, heights: Vec<f32>) -> Option<Self> {
+        (size.min_element() > 0 && heights.len() == (size.x * size.y) as usize)
+            .then_some(Self { size, heights })
+    }
+
+    /// Bilinearly samples the normalised height at the given UV (`0.0..=1.0` on both axes).
+    pub fn sample(&self, uv: Vec2) -> f32 {
+        let max = (self.size - UVec2::ONE).as_vec2();
+        let position = uv.clamp(Vec2::ZERO, Vec2::ONE) * max;
+        let min = position.floor().as_uvec2();
+        let next = (min + UVec2::ONE).min(self.size - UVec2::ONE);
+        let t = position.fract();
+
+        let height = |x: u32, y: u32| self.heights[(y * self.size.x + x) as usize];
+
+        let top = height(min.x, min.y).lerp(height(next.x, min.y), t.x);
+        let bottom = height(min.x, next.y).lerp(height(next.x, next.y), t.x);
+        top.lerp(bottom, t.y)
+    }
+
+    /// Generates a grid mesh (centred on the origin) with positions, normals and UVs.
+    /// `resolution` is the number of vertices along X & Z (minimum of 2), and is resampled from the heightmap.
+    pub fn to_mesh(&self, world_size: Vec2, height_scale: f32, resolution: UVec2) -> Mesh {
+        let resolution = resolution.max(UVec2::splat(2));
+        let cell_size = world_size / (resolution - UVec2::ONE).as_vec2();
+
+        let mut positions = Vec::with_capacity((resolution.x * resolution.y) as usize);
+        let mut uvs = Vec::with_capacity(positions.capacity());
+
+        for z in 0..resolution.y {
+            for x in 0..resolution.x {
+                let uv = UVec2::new(x, z).as_vec2() / (resolution - UVec2::ONE).as_vec2();
+                let xz = uv * world_size - world_size * 0.5;
+                positions.push([xz.x, self.sample(uv) * height_scale, xz.y]);
+                uvs.push(uv.to_array());
+            }
+        }
+
+        // Central differences (clamped at the edges)
+        let position_at =
+            |x: u32, z: u32| Vec3::from_array(positions[(z * resolution.x + x) as usize]);
+        let mut normals = Vec::with_capacity(positions.len());
+
+        for z in 0..resolution.y {
+            for x in 0..resolution.x {
+                let left = position_at(x.saturating_sub(1), z);
+                let right = position_at((x + 1).min(resolution.x - 1), z);
+                let back = position_at(x, z.saturating_sub(1));
+                let front = position_at(x, (z + 1).min(resolution.y - 1));
+
+                let dx = (right.y - left.y) / (right.x - left.x).max(cell_size.x);
+                let dz = (front.y - back.y) / (front.z - back.z).max(cell_size.y);
+                normals.push(Vec3::new(-dx, 1.0, -dz).normalize().to_array());
+            }
+        }
+
+        let mut indices =
+            Vec::with_capacity(((resolution.x - 1) * (resolution.y - 1) * 6) as usize);
+
+        for z in 0..resolution.y - 1 {
+            for x in 0..resolution.x - 1 {
+                let index = z * resolution.x + x;
+                let front = index + resolution.x;
+                indices.extend([index, front, index + 1, index + 1, front, front + 1]);
+            }
+        }
+
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::default(),
+        )
+        .with_inserted_indices(Indices::U32(indices))
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_grid_mesh() {
+        // A 2x2 heightmap which slopes upwards along X
+        let heightmap =
+            Heightmap::from_raw_r16(&[0, 0, 255, 255, 0, 0, 255, 255], UVec2::new(2, 2)).unwrap();
+
+        let mesh = heightmap.to_mesh(Vec2::new(10.0, 20.0), 5.0, UVec2::new(3, 5));
+        let positions = mesh
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+            .unwrap()
+            .as_float3()
+            .unwrap();
+
+        assert_eq!(positions.len(), 15);
+        assert_eq!(mesh.indices().unwrap().len(), 2 * 4 * 6);
+        assert_eq!(positions[0], [-5.0, 0.0, -10.0]);
+        assert_eq!(positions[1], [0.0, 2.5, -10.0]);
+        assert_eq!(positions[14], [5.0, 5.0, 10.0]);
+    }
+}
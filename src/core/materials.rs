@@ -0,0 +1,155 @@
+use super::terrain_material::*;
+use bevy::asset::*;
+use bevy::math::Affine2;
+use bevy::pbr::experimental::meshlet::*;
+use bevy::prelude::*;
+use bevy::render::texture::{
+    ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor,
+};
+use serde::{Deserialize, Serialize};
+
+/// A material referenced by [`SerialisedMeshlet::material_index`](super::SerialisedMeshlet).
+#[derive(Serialize, Deserialize, Clone)]
+pub enum SerialisedMaterial {
+    /// A material from one of the terrain's gLTF files.
+    Gltf {
+        /// Index into [`ProcessedTerrainDetails::gltf_paths`](crate::assets::ProcessedTerrainDetails).
+        source: usize,
+        /// Index of the material within that gLTF.
+        material: usize,
+    },
+    /// A material declared in the terrain RON file (e.g. for heightmap terrain).
+    Standard(SerialisedStandardMaterial),
+    /// A [`TerrainMaterial`] declared in the terrain RON file.
+    Terrain(SerialisedTerrainMaterial),
+}
+
+/// Handle to any of the material types terrain meshlets can be rendered with.
+#[derive(Clone, Debug)]
+pub enum TerrainMaterialHandle {
+    Standard(Handle<StandardMaterial>),
+    Terrain(Handle<TerrainMaterial>),
+}
+
+impl TerrainMaterialHandle {
+    pub fn id(&self) -> UntypedAssetId {
+        match self {
+            Self::Standard(handle) => handle.id().untyped(),
+            Self::Terrain(handle) => handle.id().untyped(),
+        }
+    }
+
+    /// Spawns a meshlet mesh with this material (using the MaterialMeshletMeshBundle of the material's type).
+    pub fn spawn_meshlet(&self, meshlet_handle: Handle<MeshletMesh>, parent: &mut ChildBuilder) {
+        match self {
+            Self::Standard(handle) => {
+                parent.spawn(MaterialMeshletMeshBundle {
+                    meshlet_mesh: meshlet_handle,
+                    material: handle.clone(),
+                    ..default()
+                });
+            }
+            Self::Terrain(handle) => {
+                parent.spawn(MaterialMeshletMeshBundle {
+                    meshlet_mesh: meshlet_handle,
+                    material: handle.clone(),
+                    ..default()
+                });
+            }
+        }
+    }
+}
+
+impl From<Handle<StandardMaterial>> for TerrainMaterialHandle {
+    fn from(handle: Handle<StandardMaterial>) -> Self {
+        Self::Standard(handle)
+    }
+}
+
+impl From<Handle<TerrainMaterial>> for TerrainMaterialHandle {
+    fn from(handle: Handle<TerrainMaterial>) -> Self {
+        Self::Terrain(handle)
+    }
+}
+
+/// Serialisable subset of [`StandardMaterial`], with textures referenced by asset path.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SerialisedStandardMaterial {
+    pub base_color: Color,
+    pub base_color_texture: Option<String>,
+    pub normal_map_texture: Option<String>,
+    pub metallic_roughness_texture: Option<String>,
+    pub perceptual_roughness: f32,
+    pub metallic: f32,
+    /// Number of times the textures repeat across the mesh's UVs.
+    pub uv_scale: Vec2,
+}
+
+impl Default for SerialisedStandardMaterial {
+    fn default() -> Self {
+        Self {
+            base_color: Color::WHITE,
+            base_color_texture: None,
+            normal_map_texture: None,
+            metallic_roughness_texture: None,
+            perceptual_roughness: 1.0,
+            metallic: 0.0,
+            uv_scale: Vec2::ONE,
+        }
+    }
+}
+
+impl SerialisedStandardMaterial {
+    /// Adds the material as a labeled asset, and starts loading its textures.
+    pub fn load(
+        &self,
+        label: String,
+        load_context: &mut LoadContext<'_>,
+    ) -> Handle<StandardMaterial> {
+        let material = self.to_material(load_context);
+        load_context.add_labeled_asset(label, material)
+    }
+
+    /// Converts to a StandardMaterial, and starts loading its textures.
+    pub fn to_material(&self, load_context: &mut LoadContext<'_>) -> StandardMaterial {
+        StandardMaterial {
+            base_color: self.base_color,
+            base_color_texture: self
+                .base_color_texture
+                .as_ref()
+                .map(|path| load_tiled_texture(path, true, load_context)),
+            normal_map_texture: self
+                .normal_map_texture
+                .as_ref()
+                .map(|path| load_tiled_texture(path, false, load_context)),
+            metallic_roughness_texture: self
+                .metallic_roughness_texture
+                .as_ref()
+                .map(|path| load_tiled_texture(path, false, load_context)),
+            perceptual_roughness: self.perceptual_roughness,
+            metallic: self.metallic,
+            uv_transform: Affine2::from_scale(self.uv_scale),
+            ..default()
+        }
+    }
+}
+
+/// Loads a texture with a repeating sampler, so it can be tiled across the terrain.
+pub fn load_tiled_texture(
+    path: &str,
+    is_srgb: bool,
+    load_context: &mut LoadContext<'_>,
+) -> Handle<Image> {
+    load_context
+        .loader()
+        .with_settings(move |settings: &mut ImageLoaderSettings| {
+            settings.is_srgb = is_srgb;
+            settings.sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+                address_mode_u: ImageAddressMode::Repeat,
+                address_mode_v: ImageAddressMode::Repeat,
+                ..ImageSamplerDescriptor::linear()
+            });
+        })
+        .load(path)
+}
@@ -1,3 +1,4 @@
+use super::materials::*;
 use super::serialised_meshlet_scene::*;
 use bevy::asset::*;
 use bevy::pbr::experimental::meshlet::*;
@@ -8,15 +9,15 @@ pub struct MeshletScene {
 }
 
 impl MeshletScene {
-    pub fn load<'a>(
+    pub fn load(
         serialised_nodes: Vec<SerialisedMeshletNode>,
-        gltf: &Gltf,
-        load_context: &'a mut LoadContext<'_>,
+        meshlet_meshes: &[Handle<MeshletMesh>],
+        materials: &[TerrainMaterialHandle],
     ) -> Self {
         Self {
             nodes: serialised_nodes
                 .into_iter()
-                .map(|node| MeshletNode::load(node, gltf, load_context, 0))
+                .map(|node| MeshletNode::load(node, meshlet_meshes, materials))
                 .collect(),
         }
     }
@@ -41,47 +42,56 @@ pub struct MeshletNode {
 }
 
 impl MeshletNode {
-    pub fn load<'a>(
+    /// Looks up the (shared) meshlet mesh & material handles of the node and its children.
+    /// The mesh & material indices must have been validated (see `ProcessedTerrainDetails::validate_meshlet_indices`).
+    pub fn load(
         node: SerialisedMeshletNode,
-        gltf: &Gltf,
-        load_context: &'a mut LoadContext<'_>,
-        level: usize,
+        meshlet_meshes: &[Handle<MeshletMesh>],
+        materials: &[TerrainMaterialHandle],
     ) -> Self {
         Self {
             meshlets: node
                 .meshes
                 .into_iter()
-                .enumerate()
-                .map(|(index, mesh)| MeshletMaterialPair {
-                    meshlet_handle: load_context
-                        .add_labeled_asset(format!("meshlet{0}-{1}", level, index), mesh.mesh),
-                    material_handle: gltf.materials[mesh.material_index].clone(),
+                .map(|mesh| MeshletMaterialPair {
+                    meshlet_handle: meshlet_meshes[mesh.mesh_index].clone(),
+                    material_handle: materials[mesh.material_index].clone(),
                 })
                 .collect(),
             transform: node.transform,
             children: node
                 .children
                 .into_iter()
-                .map(|child| MeshletNode::load(child, gltf, load_context, level.saturating_add(1)))
+                .map(|child| MeshletNode::load(child, meshlet_meshes, materials))
                 .collect(),
         }
     }
 
-    pub fn spawn(&self, parent: &mut ChildBuilder) {
+    /// Adds the meshlet & material asset ids of this node and its children.
+    pub fn collect_asset_ids(&self, ids: &mut Vec<UntypedAssetId>) {
         for meshlet in &self.meshlets {
-            parent.spawn(MaterialMeshletMeshBundle {
-                meshlet_mesh: meshlet.meshlet_handle.clone(),
-                material: meshlet.material_handle.clone(),
-                transform: self.transform,
-                ..default()
-            });
+            ids.push(meshlet.meshlet_handle.id().untyped());
+            ids.push(meshlet.material_handle.id());
         }
 
+        for child in &self.children {
+            child.collect_asset_ids(ids);
+        }
+    }
+
+    /// Spawns an entity with this node's transform, with the meshlets and child nodes underneath it.
+    pub fn spawn(&self, parent: &mut ChildBuilder) {
         parent
-            .spawn(SpatialBundle::default())
-            .with_children(|inner_parent| {
+            .spawn(SpatialBundle::from_transform(self.transform))
+            .with_children(|node_parent| {
+                for meshlet in &self.meshlets {
+                    meshlet
+                        .material_handle
+                        .spawn_meshlet(meshlet.meshlet_handle.clone(), node_parent);
+                }
+
                 for child in &self.children {
-                    child.spawn(inner_parent)
+                    child.spawn(node_parent)
                 }
             });
     }
@@ -89,5 +99,89 @@ impl MeshletNode {
 
 pub struct MeshletMaterialPair {
     meshlet_handle: Handle<MeshletMesh>,
-    material_handle: Handle<StandardMaterial>,
+    material_handle: TerrainMaterialHandle,
+}
+
+impl MeshletMaterialPair {
+    pub fn meshlet_handle(&self) -> &Handle<MeshletMesh> {
+        &self.meshlet_handle
+    }
+
+    pub fn material_handle(&self) -> &TerrainMaterialHandle {
+        &self.material_handle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    fn test_node(id: u128, transform: Transform, children: Vec<MeshletNode>) -> MeshletNode {
+        MeshletNode {
+            meshlets: vec![MeshletMaterialPair {
+                meshlet_handle: Handle::weak_from_u128(id),
+                material_handle: TerrainMaterialHandle::Standard(Handle::default()),
+            }],
+            transform,
+            children,
+        }
+    }
+
+    #[test]
+    fn meshlets_inherit_node_hierarchy_transforms() {
+        // Mirrors a gLTF hierarchy of Root -> Child -> Grandchild, plus a sibling of Child
+        let root_transform =
+            Transform::from_xyz(10.0, 0.0, 0.0).with_rotation(Quat::from_rotation_y(FRAC_PI_2));
+        let child_transform = Transform::from_xyz(0.0, 5.0, 2.0).with_scale(Vec3::splat(2.0));
+        let grandchild_transform = Transform::from_xyz(1.0, 0.0, -3.0);
+        let sibling_transform = Transform::from_xyz(0.0, 5.0, 2.0);
+
+        let scene = MeshletScene {
+            nodes: vec![test_node(
+                1,
+                root_transform,
+                vec![
+                    test_node(
+                        2,
+                        child_transform,
+                        vec![test_node(3, grandchild_transform, Vec::new())],
+                    ),
+                    test_node(4, sibling_transform, Vec::new()),
+                ],
+            )],
+        };
+
+        let mut app = App::new();
+        app.add_plugins((TransformPlugin, HierarchyPlugin));
+        scene.spawn(&mut app.world_mut().commands());
+        app.world_mut().flush();
+        app.update();
+
+        let root = GlobalTransform::from(root_transform);
+        let child = root.mul_transform(child_transform);
+        let expected = [
+            (1, root),
+            (2, child),
+            (3, child.mul_transform(grandchild_transform)),
+            (4, root.mul_transform(sibling_transform)),
+        ];
+
+        let mut query = app
+            .world_mut()
+            .query::<(&Handle<MeshletMesh>, &GlobalTransform)>();
+        let world = app.world();
+        assert_eq!(query.iter(world).count(), expected.len());
+
+        for (id, expected_transform) in expected {
+            let (_, global_transform) = query
+                .iter(world)
+                .find(|(handle, _)| handle.id() == Handle::<MeshletMesh>::weak_from_u128(id).id())
+                .unwrap();
+
+            assert!(global_transform
+                .affine()
+                .abs_diff_eq(expected_transform.affine(), 1e-5));
+        }
+    }
 }
@@ -1,9 +1,25 @@
+mod auto_texturing;
+mod chunks;
 mod colliders;
+mod collision_mesh;
+mod gltf_extras;
+mod heightfield;
+mod heightmap;
+mod materials;
 mod meshlet_scene;
 mod serialised_meshlet_scene;
 mod terrain_details;
+mod terrain_material;
 
+pub use self::auto_texturing::*;
+pub use self::chunks::*;
 pub use self::colliders::*;
+pub use self::collision_mesh::*;
+pub use self::gltf_extras::*;
+pub use self::heightfield::*;
+pub use self::heightmap::*;
+pub use self::materials::*;
 pub use self::meshlet_scene::*;
 pub use self::serialised_meshlet_scene::*;
 pub use self::terrain_details::*;
+pub use self::terrain_material::*;
@@ -1,9 +1,11 @@
-use bevy::pbr::experimental::meshlet::*;
+use bevy::pbr::experimental::meshlet::MeshletMesh;
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
 pub struct SerialisedMeshletNode {
+    /// The chain of gLTF node indices leading to this node (e.g. `Node0/Node3`).
+    pub path: String,
     pub meshes: Vec<SerialisedMeshlet>,
     pub transform: Transform,
     pub children: Vec<SerialisedMeshletNode>,
@@ -11,6 +13,33 @@ pub struct SerialisedMeshletNode {
 
 #[derive(Serialize, Deserialize)]
 pub struct SerialisedMeshlet {
-    pub mesh: MeshletMesh,
+    /// Index into the terrain's meshlet meshes (shared by every node using the same gLTF mesh).
+    pub mesh_index: usize,
     pub material_index: usize,
+    /// Index of the gLTF primitive this meshlet was generated from.
+    pub primitive_index: usize,
+}
+
+/// Returns the path of a gLTF node within the terrain (see [`SerialisedMeshletNode::path`]).
+/// The chain of node indices is unique within the scene, and stable across reprocessing.
+pub fn meshlet_node_path(parent_path: Option<&str>, node_index: usize) -> String {
+    match parent_path {
+        Some(parent_path) => format!("{0}/Node{1}", parent_path, node_index),
+        None => format!("Node{}", node_index),
+    }
+}
+
+/// A unique meshlet mesh, with the sub-asset label it's added with.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SerialisedMeshletMesh {
+    /// See [`meshlet_mesh_label`].
+    pub label: String,
+    pub mesh: MeshletMesh,
+}
+
+/// Returns the sub-asset label of a terrain meshlet mesh, from the path of what it's generated from
+/// (e.g. `default.terrain.bin#Meshlet/Gltf0/Mesh2/Primitive1`, `#Meshlet/Heightmap` or `#Meshlet/Chunk1_-2/Primitive0`).
+/// Labels don't depend on the order meshes are processed in, so they're stable across reprocessing.
+pub fn meshlet_mesh_label(source_path: &str) -> String {
+    format!("Meshlet/{}", source_path)
 }
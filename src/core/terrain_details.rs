@@ -1,12 +1,41 @@
+use super::chunks::*;
 use super::colliders::*;
 use super::meshlet_scene::*;
 use bevy::asset::*;
 use bevy::prelude::*;
+use bevy::utils::HashSet;
 
 /// Stores terrain meshlets and colliders
 #[derive(Asset, TypePath)]
 pub struct TerrainDetails {
-    pub _gltf_handle: Handle<Gltf>,
+    /// Keeps the gLTFs (and so their materials) loaded whilst the terrain is.
+    pub gltf_handles: Vec<Handle<Gltf>>,
     pub meshlet_scene: MeshletScene,
     pub colliders: Vec<TerrainColliderNode>,
+    /// The size of each chunk, if the terrain was split into chunks.
+    pub chunk_size: Option<f32>,
+    pub chunks: Vec<Handle<TerrainChunk>>,
+}
+
+impl TerrainDetails {
+    /// Returns the ids of the assets used by the terrain (the gLTFs, meshlets, materials & chunks), without duplicates.
+    pub fn asset_ids(&self) -> Vec<UntypedAssetId> {
+        let mut ids = Vec::new();
+
+        ids.extend(
+            self.gltf_handles
+                .iter()
+                .map(|gltf_handle| gltf_handle.id().untyped()),
+        );
+
+        for node in &self.meshlet_scene.nodes {
+            node.collect_asset_ids(&mut ids);
+        }
+
+        ids.extend(self.chunks.iter().map(|chunk| chunk.id().untyped()));
+
+        let mut unique_ids = HashSet::new();
+        ids.retain(|id| unique_ids.insert(*id));
+        ids
+    }
 }
@@ -0,0 +1,176 @@
+use super::auto_texturing::*;
+use super::materials::*;
+use bevy::asset::*;
+use bevy::pbr::{ExtendedMaterial, MaterialExtension};
+use bevy::prelude::*;
+use bevy::render::render_resource::{AsBindGroup, ShaderRef, ShaderType};
+use bevy::render::texture::ImageLoaderSettings;
+use serde::{Deserialize, Serialize};
+
+/// Number of texture layers blended by [`TerrainMaterial`] (one per channel of the splat map).
+pub const TERRAIN_MATERIAL_LAYERS: usize = 4;
+
+const TERRAIN_MATERIAL_SHADER_PATH: &str =
+    "embedded://meshlet_terrain_testing/core/terrain_material.wgsl";
+
+/// StandardMaterial extended with terrain specific shading.
+pub type TerrainMaterial = ExtendedMaterial<StandardMaterial, TerrainMaterialExtension>;
+
+/// Registers [`TerrainMaterial`] and embeds its shader.
+pub struct TerrainMaterialPlugin;
+
+impl Plugin for TerrainMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "terrain_material.wgsl");
+        app.add_plugins(MaterialPlugin::<TerrainMaterial>::default());
+    }
+}
+
+/// The terrain specific part of [`TerrainMaterial`].
+/// Blends up to four texture layers using the RGBA channels of a splat map.
+/// The alpha channel of each layer's texture is its height, which is used to blend between layers.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone, Default)]
+pub struct TerrainMaterialExtension {
+    #[uniform(100)]
+    pub settings: TerrainMaterialSettings,
+    #[texture(101)]
+    #[sampler(102)]
+    pub splat_map: Option<Handle<Image>>,
+    #[texture(103)]
+    #[sampler(104)]
+    pub layer_0: Option<Handle<Image>>,
+    #[texture(105)]
+    #[sampler(106)]
+    pub layer_1: Option<Handle<Image>>,
+    #[texture(107)]
+    #[sampler(108)]
+    pub layer_2: Option<Handle<Image>>,
+    #[texture(109)]
+    #[sampler(110)]
+    pub layer_3: Option<Handle<Image>>,
+}
+
+impl MaterialExtension for TerrainMaterialExtension {
+    fn fragment_shader() -> ShaderRef {
+        TERRAIN_MATERIAL_SHADER_PATH.into()
+    }
+
+    fn meshlet_mesh_fragment_shader() -> ShaderRef {
+        TERRAIN_MATERIAL_SHADER_PATH.into()
+    }
+}
+
+#[derive(ShaderType, Reflect, Debug, Clone)]
+pub struct TerrainMaterialSettings {
+    /// Number of times each layer repeats across the mesh's UVs.
+    pub layer_tiling: Vec4,
+    /// Height range over which layers blend (smaller values give sharper transitions).
+    pub height_blend: f32,
+    /// Number of layers with a texture.
+    pub layer_count: u32,
+}
+
+impl Default for TerrainMaterialSettings {
+    fn default() -> Self {
+        Self {
+            layer_tiling: Vec4::ONE,
+            height_blend: 0.2,
+            layer_count: 0,
+        }
+    }
+}
+
+/// Serialisable version of [`TerrainMaterial`].
+/// Unknown fields are rejected, so e.g. `texturing_rules` (which only the heightmap supports) isn't silently ignored.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct SerialisedTerrainMaterial {
+    pub base: SerialisedStandardMaterial,
+    /// RGBA weight texture, each channel selecting one of the layers (spans the mesh's UVs once).
+    pub splat_map: Option<String>,
+    /// Up to [`TERRAIN_MATERIAL_LAYERS`] layers.
+    pub layers: Vec<SerialisedTerrainLayer>,
+    /// See [`TerrainMaterialSettings::height_blend`].
+    pub height_blend: f32,
+    /// Generated from [`TerrainLayerRule`]s during processing, and used if `splat_map` isn't set.
+    pub baked_splat_map: Option<BakedSplatMap>,
+}
+
+impl Default for SerialisedTerrainMaterial {
+    fn default() -> Self {
+        Self {
+            base: SerialisedStandardMaterial::default(),
+            splat_map: None,
+            layers: Vec::new(),
+            height_blend: TerrainMaterialSettings::default().height_blend,
+            baked_splat_map: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SerialisedTerrainLayer {
+    /// Base colour texture, with the layer's height in the alpha channel.
+    pub texture: String,
+    /// Number of times the texture repeats across the mesh's UVs.
+    #[serde(default = "default_layer_tiling")]
+    pub tiling: f32,
+}
+
+fn default_layer_tiling() -> f32 {
+    1.0
+}
+
+impl SerialisedTerrainMaterial {
+    /// Adds the material as a labeled asset, and starts loading its textures.
+    pub fn load(
+        &self,
+        label: String,
+        load_context: &mut LoadContext<'_>,
+    ) -> Handle<TerrainMaterial> {
+        let mut layer_tiling = [1.0; TERRAIN_MATERIAL_LAYERS];
+        let mut layer_textures: [Option<Handle<Image>>; TERRAIN_MATERIAL_LAYERS] = default();
+
+        for (index, layer) in self.layers.iter().take(TERRAIN_MATERIAL_LAYERS).enumerate() {
+            layer_tiling[index] = layer.tiling;
+            layer_textures[index] = Some(load_tiled_texture(&layer.texture, true, load_context));
+        }
+
+        let [layer_0, layer_1, layer_2, layer_3] = layer_textures;
+
+        let splat_map = match (&self.splat_map, &self.baked_splat_map) {
+            (Some(path), _) => Some(
+                // The weights are linear, so the splat map mustn't be treated as sRGB
+                load_context
+                    .loader()
+                    .with_settings(|settings: &mut ImageLoaderSettings| {
+                        settings.is_srgb = false;
+                    })
+                    .load(path),
+            ),
+            (None, Some(baked_splat_map)) => Some(
+                load_context
+                    .add_labeled_asset(format!("{}/SplatMap", label), baked_splat_map.to_image()),
+            ),
+            (None, None) => None,
+        };
+
+        let material = TerrainMaterial {
+            base: self.base.to_material(load_context),
+            extension: TerrainMaterialExtension {
+                settings: TerrainMaterialSettings {
+                    layer_tiling: Vec4::from_array(layer_tiling),
+                    height_blend: self.height_blend,
+                    layer_count: self.layers.len().min(TERRAIN_MATERIAL_LAYERS) as u32,
+                },
+                splat_map,
+                layer_0,
+                layer_1,
+                layer_2,
+                layer_3,
+            },
+        };
+
+        load_context.add_labeled_asset(label, material)
+    }
+}
@@ -0,0 +1,141 @@
+// Fragment shader for TerrainMaterial.
+// Blends the splat map's layers by weight & height, then shades the result like a StandardMaterial.
+
+#import bevy_pbr::{
+    pbr_fragment::pbr_input_from_standard_material,
+    pbr_functions::alpha_discard,
+}
+
+#ifdef PREPASS_PIPELINE
+#import bevy_pbr::{
+    prepass_io::{VertexOutput, FragmentOutput},
+    pbr_deferred_functions::deferred_output,
+}
+#else
+#import bevy_pbr::{
+    forward_io::{VertexOutput, FragmentOutput},
+    pbr_functions::{apply_pbr_lighting, main_pass_post_lighting_processing},
+    pbr_types::STANDARD_MATERIAL_FLAGS_UNLIT_BIT,
+}
+#endif
+
+#ifdef MESHLET_MESH_MATERIAL_PASS
+#import bevy_pbr::meshlet_visibility_buffer_resolve::resolve_vertex_output
+#endif
+
+struct TerrainMaterialSettings {
+    layer_tiling: vec4<f32>,
+    height_blend: f32,
+    layer_count: u32,
+}
+
+@group(2) @binding(100) var<uniform> terrain: TerrainMaterialSettings;
+@group(2) @binding(101) var splat_map: texture_2d<f32>;
+@group(2) @binding(102) var splat_map_sampler: sampler;
+@group(2) @binding(103) var layer_0: texture_2d<f32>;
+@group(2) @binding(104) var layer_0_sampler: sampler;
+@group(2) @binding(105) var layer_1: texture_2d<f32>;
+@group(2) @binding(106) var layer_1_sampler: sampler;
+@group(2) @binding(107) var layer_2: texture_2d<f32>;
+@group(2) @binding(108) var layer_2_sampler: sampler;
+@group(2) @binding(109) var layer_3: texture_2d<f32>;
+@group(2) @binding(110) var layer_3_sampler: sampler;
+
+// Meshlets don't have hardware derivatives, so all textures are sampled with explicit gradients
+fn sample_layer(
+    layer: texture_2d<f32>,
+    layer_sampler: sampler,
+    tiling: f32,
+    uv: vec2<f32>,
+    ddx_uv: vec2<f32>,
+    ddy_uv: vec2<f32>,
+) -> vec4<f32> {
+    return textureSampleGrad(layer, layer_sampler, uv * tiling, ddx_uv * tiling, ddy_uv * tiling);
+}
+
+// Returns the colour of the layers, blended by the splat map weights and each layer's height (alpha)
+fn blend_layers(uv: vec2<f32>, ddx_uv: vec2<f32>, ddy_uv: vec2<f32>) -> vec3<f32> {
+    let weights = textureSampleGrad(splat_map, splat_map_sampler, uv, ddx_uv, ddy_uv);
+    let tiling = terrain.layer_tiling;
+
+    let layers = array<vec4<f32>, 4>(
+        sample_layer(layer_0, layer_0_sampler, tiling.x, uv, ddx_uv, ddy_uv),
+        sample_layer(layer_1, layer_1_sampler, tiling.y, uv, ddx_uv, ddy_uv),
+        sample_layer(layer_2, layer_2_sampler, tiling.z, uv, ddx_uv, ddy_uv),
+        sample_layer(layer_3, layer_3_sampler, tiling.w, uv, ddx_uv, ddy_uv),
+    );
+
+    var heights = vec4(
+        layers[0].a + weights.x,
+        layers[1].a + weights.y,
+        layers[2].a + weights.z,
+        layers[3].a + weights.w,
+    );
+
+    // Layers without a texture, or that aren't painted here, are never visible
+    // (otherwise a high texture alpha alone could push them above the painted layers)
+    for (var index = 0u; index < 4u; index += 1u) {
+        if index >= terrain.layer_count || weights[index] <= 0.0 {
+            heights[index] = -1.0;
+        }
+    }
+
+    let blend = max(terrain.height_blend, 0.0001);
+    let threshold = max(max(heights.x, heights.y), max(heights.z, heights.w)) - blend;
+    let blend_weights = max(heights - vec4(threshold), vec4(0.0));
+
+    let color = layers[0].rgb * blend_weights.x
+        + layers[1].rgb * blend_weights.y
+        + layers[2].rgb * blend_weights.z
+        + layers[3].rgb * blend_weights.w;
+
+    return color / max(dot(blend_weights, vec4(1.0)), 0.0001);
+}
+
+@fragment
+fn fragment(
+#ifdef MESHLET_MESH_MATERIAL_PASS
+    @builtin(position) frag_coord: vec4<f32>,
+#else
+    in: VertexOutput,
+    @builtin(front_facing) is_front: bool,
+#endif
+) -> FragmentOutput {
+#ifdef MESHLET_MESH_MATERIAL_PASS
+    let in = resolve_vertex_output(frag_coord);
+    let is_front = true;
+#endif
+
+    var pbr_input = pbr_input_from_standard_material(in, is_front);
+
+    if terrain.layer_count > 0u {
+#ifdef MESHLET_MESH_MATERIAL_PASS
+        let layer_color = blend_layers(in.uv, in.ddx_uv, in.ddy_uv);
+#else ifdef VERTEX_UVS_A
+        let layer_color = blend_layers(in.uv, dpdx(in.uv), dpdy(in.uv));
+#else
+        let layer_color = vec3(1.0);
+#endif
+        pbr_input.material.base_color = vec4(
+            pbr_input.material.base_color.rgb * layer_color,
+            pbr_input.material.base_color.a,
+        );
+    }
+
+    pbr_input.material.base_color = alpha_discard(pbr_input.material, pbr_input.material.base_color);
+
+#ifdef PREPASS_PIPELINE
+    let out = deferred_output(in, pbr_input);
+#else
+    var out: FragmentOutput;
+    if (pbr_input.material.flags & STANDARD_MATERIAL_FLAGS_UNLIT_BIT) == 0u {
+        out.color = apply_pbr_lighting(pbr_input);
+    } else {
+        out.color = pbr_input.material.base_color;
+    }
+
+    out.color = main_pass_post_lighting_processing(pbr_input, out.color);
+#endif
+
+    return out;
+}
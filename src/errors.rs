@@ -1,9 +1,13 @@
-use bevy::asset::LoadDirectError;
+use bevy::asset::{LoadDirectError, ReadAssetBytesError};
+use bevy::math::IVec2;
+use bevy::pbr::experimental::meshlet::MeshToMeshletMeshConversionError;
+use bevy::render::mesh::GenerateTangentsError;
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum StartupError {
-    #[error("No path specified for terrain")]
+    #[error("No gLTF path or heightmap specified for terrain")]
     MissingGltfPath,
 }
 
@@ -17,8 +21,114 @@ pub enum LoaderError {
     PostcardError(#[from] postcard::Error),
     #[error(transparent)]
     LoadDirectError(#[from] LoadDirectError),
-    #[error("{0}")]
-    Other(String),
+    #[error(transparent)]
+    ReadAssetBytesError(#[from] ReadAssetBytesError),
+    #[error(transparent)]
+    StartupError(#[from] StartupError),
+    #[error("Incompatible processed terrain file ({0}). Delete the `imported_assets` folder so the terrain is reprocessed")]
+    IncompatibleTerrainFile(String),
+    #[error("Unable to generate collider for terrain {0}")]
+    ColliderGeneration(TerrainMeshSource),
+    #[error("Unable to generate tangents for terrain {mesh} [{error}]")]
+    TangentGeneration {
+        mesh: TerrainMeshSource,
+        #[source]
+        error: GenerateTangentsError,
+    },
+    #[error("Unable to generate meshlets for terrain {mesh} [{error}]")]
+    MeshletGeneration {
+        mesh: TerrainMeshSource,
+        #[source]
+        error: MeshToMeshletMeshConversionError,
+    },
+    #[error("Chunk size must be positive (got {0})")]
+    InvalidChunkSize(f32),
+    #[error("Unsupported heightmap format or size for '{0}'")]
+    UnsupportedHeightmap(String),
+    #[error("Scene '{0}' not found in gLTF")]
+    SceneNotFound(String),
+    #[error("Unable to find scene '{0}' as the gLTF source wasn't loaded")]
+    MissingGltfSource(String),
+    #[error("Terrain material has {0} layers, but at most {max} are supported", max = crate::core::TERRAIN_MATERIAL_LAYERS)]
+    TooManyTerrainLayers(usize),
+    #[error("Terrain texturing rule uses layer {0}, but there are only {max} layers", max = crate::core::TERRAIN_MATERIAL_LAYERS)]
+    InvalidTerrainLayer(usize),
+    #[error("Heightmap texturing rules need a terrain material to bake the splat map for")]
+    TexturingRulesWithoutTerrainMaterial,
+    #[error("Missing material {material} in gLTF {gltf}")]
+    MissingGltfMaterial { gltf: usize, material: usize },
+    #[error("Invalid extras on gLTF node '{node}' [{error}]")]
+    InvalidGltfExtras {
+        node: String,
+        #[source]
+        error: serde_json::Error,
+    },
+    #[error("Invalid extras on gLTF mesh '{mesh}' [{error}]")]
+    InvalidGltfMeshExtras {
+        mesh: String,
+        #[source]
+        error: serde_json::Error,
+    },
+    #[error(
+        "gLTF node '{node}' uses physics material '{material}', which isn't defined by the terrain"
+    )]
+    MissingPhysicsMaterial { node: String, material: String },
+    #[error("gLTF node '{node}' uses collision group {group}, but groups must be less than 32")]
+    InvalidCollisionGroup { node: String, group: u32 },
+    #[error("Terrain node '{node}' uses meshlet mesh {mesh_index} & material {material_index}, but there are only {mesh_count} meshes & {material_count} materials")]
+    InvalidMeshletIndex {
+        node: String,
+        mesh_index: usize,
+        material_index: usize,
+        mesh_count: usize,
+        material_count: usize,
+    },
+    #[error("Terrain collider uses shape {shape_index}, but there are only {shape_count} shapes")]
+    InvalidColliderShapeIndex {
+        shape_index: usize,
+        shape_count: usize,
+    },
+}
+
+/// Identifies the terrain mesh primitive which failed to process.
+#[derive(Debug, Clone, Default)]
+pub struct TerrainMeshSource {
+    /// Path of the gLTF containing the mesh (`None` for heightmap terrain).
+    pub gltf_path: Option<String>,
+    /// Index of the gLTF mesh (`None` for heightmap terrain).
+    pub mesh_index: Option<usize>,
+    pub primitive_index: usize,
+    /// Name of the (first) gLTF node using the mesh.
+    pub node_name: Option<String>,
+    /// The chunk being generated, if the terrain is chunked.
+    pub chunk: Option<IVec2>,
+}
+
+impl fmt::Display for TerrainMeshSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.mesh_index {
+            Some(mesh_index) => write!(
+                f,
+                "mesh {0}, primitive {1}",
+                mesh_index, self.primitive_index
+            )?,
+            None => write!(f, "heightmap mesh")?,
+        }
+
+        if let Some(gltf_path) = &self.gltf_path {
+            write!(f, " of '{0}'", gltf_path)?;
+        }
+
+        if let Some(node_name) = &self.node_name {
+            write!(f, " (node '{0}')", node_name)?;
+        }
+
+        if let Some(chunk) = self.chunk {
+            write!(f, " in chunk {0}", chunk)?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Error, Debug)]
@@ -0,0 +1,413 @@
+use crate::assets::*;
+use crate::core::*;
+use crate::errors::*;
+use bevy::pbr::experimental::meshlet::*;
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::Collider;
+use serde::Serialize;
+use std::fmt;
+
+/// Size of each vertex in [`MeshletMesh::vertex_data`] (position, normal, UV & tangent).
+const MESHLET_VERTEX_SIZE: usize = 48;
+
+//****************************************************************************
+// REPORTS
+//****************************************************************************
+
+/// Summary of a processed terrain file, for the `terrain-inspect` tool.
+/// Displays as a human-readable tree, or can be serialised (e.g. to JSON).
+#[derive(Serialize)]
+pub struct TerrainReport {
+    pub header: TerrainFileHeader,
+    /// Size of the whole file.
+    pub file_size: usize,
+    /// Size of the decompressed terrain details.
+    pub uncompressed_size: usize,
+    pub gltf_paths: Vec<String>,
+    pub materials: Vec<String>,
+    /// Number of unique meshlet meshes (shared by nodes using the same gLTF mesh).
+    pub meshlet_meshes: usize,
+    pub meshlet_nodes: Vec<MeshletNodeReport>,
+    /// Number of unique collider shapes (shared by nodes using the same gLTF mesh).
+    pub collider_shapes: usize,
+    pub collider_nodes: Vec<ColliderNodeReport>,
+    pub chunk_size: Option<f32>,
+    pub chunks: Vec<ChunkReport>,
+}
+
+#[derive(Serialize)]
+pub struct MeshletNodeReport {
+    pub path: String,
+    pub transform: Transform,
+    pub meshes: Vec<MeshletReport>,
+    pub children: Vec<MeshletNodeReport>,
+}
+
+#[derive(Serialize)]
+pub struct MeshletReport {
+    pub primitive_index: usize,
+    pub mesh_index: usize,
+    pub material_index: usize,
+    /// Number of meshlets across all LODs.
+    pub meshlets: usize,
+    pub vertices: usize,
+    /// Number of triangles at full detail.
+    pub triangles: u64,
+}
+
+#[derive(Serialize)]
+pub struct ColliderNodeReport {
+    pub collider_shape: TerrainColliderShape,
+    pub physics_material: Option<TerrainPhysicsMaterial>,
+    pub collision_group: Option<u32>,
+    pub transform: Transform,
+    pub colliders: Vec<ColliderReport>,
+    pub children: Vec<ColliderNodeReport>,
+}
+
+#[derive(Serialize)]
+pub struct ColliderReport {
+    pub shape_index: usize,
+    pub shape: String,
+    /// Size of the collider's local bounding box.
+    pub size: Vec3,
+    /// Shape specific details (e.g. the number of triangles of a trimesh).
+    pub details: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ChunkReport {
+    pub coord: IVec2,
+    pub min: Vec3,
+    pub max: Vec3,
+    pub meshlet_node: MeshletNodeReport,
+    pub collider_node: ColliderNodeReport,
+}
+
+impl TerrainReport {
+    /// Decodes a processed terrain file (as written to the `imported_assets` folder).
+    /// Returns an error (rather than panicking) if the file refers to meshes, materials or collider shapes it doesn't contain.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LoaderError> {
+        let (header, payload) = decompress_processed_terrain(bytes)?;
+        let terrain = migrate_terrain_payload(header.format_version, &payload)?;
+        terrain.validate_meshlet_indices()?;
+        terrain.validate_collider_shape_indices()?;
+
+        Ok(Self {
+            header,
+            file_size: bytes.len(),
+            uncompressed_size: payload.len(),
+            gltf_paths: terrain.gltf_paths,
+            materials: terrain
+                .materials
+                .iter()
+                .map(|material| match material {
+                    SerialisedMaterial::Gltf { source, material } => {
+                        format!("gLTF {0} material {1}", source, material)
+                    }
+                    SerialisedMaterial::Standard(material) => format!(
+                        "Standard material (base colour texture: {})",
+                        material.base_color_texture.as_deref().unwrap_or("none")
+                    ),
+                    SerialisedMaterial::Terrain(material) => format!(
+                        "Terrain material (base colour texture: {})",
+                        material
+                            .base
+                            .base_color_texture
+                            .as_deref()
+                            .unwrap_or("none")
+                    ),
+                })
+                .collect(),
+            meshlet_meshes: terrain.meshlet_meshes.len(),
+            meshlet_nodes: terrain
+                .meshlet_nodes
+                .iter()
+                .map(|node| MeshletNodeReport::new(node, &terrain.meshlet_meshes))
+                .collect(),
+            collider_shapes: terrain.collider_shapes.len(),
+            collider_nodes: terrain
+                .colliders
+                .iter()
+                .map(|node| ColliderNodeReport::new(node, &terrain.collider_shapes))
+                .collect(),
+            chunk_size: terrain.chunk_size,
+            chunks: terrain
+                .chunks
+                .iter()
+                .map(|chunk| ChunkReport {
+                    coord: chunk.coord,
+                    min: chunk.min,
+                    max: chunk.max,
+                    meshlet_node: MeshletNodeReport::new(
+                        &chunk.meshlet_node,
+                        &terrain.meshlet_meshes,
+                    ),
+                    collider_node: ColliderNodeReport::new(
+                        &chunk.collider_node,
+                        &terrain.collider_shapes,
+                    ),
+                })
+                .collect(),
+        })
+    }
+
+    /// Returns the meshlet nodes (including those of chunks).
+    fn all_meshlet_nodes(&self) -> impl Iterator<Item = &MeshletNodeReport> {
+        self.meshlet_nodes
+            .iter()
+            .chain(self.chunks.iter().map(|chunk| &chunk.meshlet_node))
+    }
+
+    /// Returns the collider nodes (including those of chunks).
+    fn all_collider_nodes(&self) -> impl Iterator<Item = &ColliderNodeReport> {
+        self.collider_nodes
+            .iter()
+            .chain(self.chunks.iter().map(|chunk| &chunk.collider_node))
+    }
+}
+
+impl MeshletNodeReport {
+    fn new(node: &SerialisedMeshletNode, meshlet_meshes: &[SerialisedMeshletMesh]) -> Self {
+        Self {
+            path: node.path.clone(),
+            transform: node.transform,
+            meshes: node
+                .meshes
+                .iter()
+                .map(|meshlet| {
+                    let mesh = &meshlet_meshes[meshlet.mesh_index].mesh;
+
+                    MeshletReport {
+                        primitive_index: meshlet.primitive_index,
+                        mesh_index: meshlet.mesh_index,
+                        material_index: meshlet.material_index,
+                        meshlets: mesh.meshlets.len(),
+                        vertices: mesh.vertex_data.len() / MESHLET_VERTEX_SIZE,
+                        triangles: mesh.worst_case_meshlet_triangles,
+                    }
+                })
+                .collect(),
+            children: node
+                .children
+                .iter()
+                .map(|child| Self::new(child, meshlet_meshes))
+                .collect(),
+        }
+    }
+
+    /// Sums the meshlets, vertices & triangles of this node and its descendants.
+    fn totals(&self) -> (usize, usize, u64) {
+        self.children.iter().map(Self::totals).fold(
+            self.meshes.iter().fold((0, 0, 0), |totals, mesh| {
+                (
+                    totals.0 + mesh.meshlets,
+                    totals.1 + mesh.vertices,
+                    totals.2 + mesh.triangles,
+                )
+            }),
+            |totals, child| (totals.0 + child.0, totals.1 + child.1, totals.2 + child.2),
+        )
+    }
+
+    fn fmt_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        writeln!(
+            f,
+            "{0}{1} (translation: {2})",
+            indent, self.path, self.transform.translation
+        )?;
+
+        for mesh in &self.meshes {
+            writeln!(
+                f,
+                "{0}  - Primitive{1}: mesh {2}, material {3}, {4} meshlets, {5} vertices, {6} triangles",
+                indent,
+                mesh.primitive_index,
+                mesh.mesh_index,
+                mesh.material_index,
+                mesh.meshlets,
+                mesh.vertices,
+                mesh.triangles
+            )?;
+        }
+
+        for child in &self.children {
+            child.fmt_tree(f, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ColliderNodeReport {
+    fn new(node: &SerialisedTerrainColliderNode, collider_shapes: &[Collider]) -> Self {
+        Self {
+            collider_shape: node.collider_shape,
+            physics_material: node.physics_material,
+            collision_group: node.collision_group,
+            transform: node.transform,
+            colliders: node
+                .colliders
+                .iter()
+                .map(|collider| ColliderReport::new(collider, collider_shapes))
+                .collect(),
+            children: node
+                .children
+                .iter()
+                .map(|child| Self::new(child, collider_shapes))
+                .collect(),
+        }
+    }
+
+    fn count(&self) -> usize {
+        self.colliders.len() + self.children.iter().map(Self::count).sum::<usize>()
+    }
+
+    fn fmt_tree(&self, f: &mut fmt::Formatter<'_>, depth: usize) -> fmt::Result {
+        let indent = "  ".repeat(depth);
+        write!(
+            f,
+            "{0}Node ({1:?} colliders, translation: {2}",
+            indent, self.collider_shape, self.transform.translation
+        )?;
+
+        if let Some(physics_material) = &self.physics_material {
+            write!(
+                f,
+                ", friction: {0}, restitution: {1}",
+                physics_material.friction, physics_material.restitution
+            )?;
+        }
+
+        if let Some(collision_group) = self.collision_group {
+            write!(f, ", collision group: {}", collision_group)?;
+        }
+
+        writeln!(f, ")")?;
+
+        for collider in &self.colliders {
+            write!(
+                f,
+                "{0}  - {1} (shape {2}): size {3}",
+                indent, collider.shape, collider.shape_index, collider.size
+            )?;
+
+            match &collider.details {
+                Some(details) => writeln!(f, ", {}", details)?,
+                None => writeln!(f)?,
+            }
+        }
+
+        for child in &self.children {
+            child.fmt_tree(f, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ColliderReport {
+    fn new(collider: &SerialisedTerrainCollider, collider_shapes: &[Collider]) -> Self {
+        let shape = &collider_shapes[collider.shape_index].raw;
+        let extents = shape.compute_local_aabb().extents();
+
+        let details = if let Some(trimesh) = shape.as_trimesh() {
+            Some(format!("{} triangles", trimesh.num_triangles()))
+        } else if let Some(heightfield) = shape.as_heightfield() {
+            Some(format!(
+                "{0}x{1} heights",
+                heightfield.ncols(),
+                heightfield.nrows()
+            ))
+        } else if let Some(compound) = shape.as_compound() {
+            Some(format!("{} parts", compound.shapes().len()))
+        } else {
+            shape
+                .as_convex_polyhedron()
+                .map(|convex| format!("{} vertices", convex.points().len()))
+        };
+
+        Self {
+            shape_index: collider.shape_index,
+            shape: format!("{:?}", shape.shape_type()),
+            size: Vec3::new(extents.x, extents.y, extents.z),
+            details,
+        }
+    }
+}
+
+impl fmt::Display for TerrainReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Format version {0} (crate {1}, Bevy {2}, meshlet version {3})",
+            self.header.format_version,
+            self.header.crate_version,
+            self.header.bevy_version,
+            self.header.meshlet_mesh_version
+        )?;
+        writeln!(
+            f,
+            "Size: {0} bytes ({1} bytes uncompressed)",
+            self.file_size, self.uncompressed_size
+        )?;
+
+        for (index, gltf_path) in self.gltf_paths.iter().enumerate() {
+            writeln!(f, "gLTF {0}: {1}", index, gltf_path)?;
+        }
+
+        let (meshlets, vertices, triangles) = self
+            .all_meshlet_nodes()
+            .map(MeshletNodeReport::totals)
+            .fold((0, 0, 0), |totals, node| {
+                (totals.0 + node.0, totals.1 + node.1, totals.2 + node.2)
+            });
+
+        writeln!(
+            f,
+            "Totals: {0} meshlets, {1} vertices, {2} triangles ({3} unique meshlet meshes), {4} colliders ({5} unique shapes)",
+            meshlets,
+            vertices,
+            triangles,
+            self.meshlet_meshes,
+            self.all_collider_nodes()
+                .map(ColliderNodeReport::count)
+                .sum::<usize>(),
+            self.collider_shapes
+        )?;
+
+        writeln!(f, "\nMaterials:")?;
+        for (index, material) in self.materials.iter().enumerate() {
+            writeln!(f, "  {0}: {1}", index, material)?;
+        }
+
+        writeln!(f, "\nMeshlet nodes:")?;
+        for node in &self.meshlet_nodes {
+            node.fmt_tree(f, 1)?;
+        }
+
+        writeln!(f, "\nCollider nodes:")?;
+        for node in &self.collider_nodes {
+            node.fmt_tree(f, 1)?;
+        }
+
+        if let Some(chunk_size) = self.chunk_size {
+            writeln!(f, "\nChunks ({} units):", chunk_size)?;
+
+            for chunk in &self.chunks {
+                writeln!(
+                    f,
+                    "  {0} (bounds: {1} to {2})",
+                    TerrainChunk::label(chunk.coord),
+                    chunk.min,
+                    chunk.max
+                )?;
+                chunk.meshlet_node.fmt_tree(f, 2)?;
+                chunk.collider_node.fmt_tree(f, 2)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+pub mod assets;
+pub mod components;
+pub mod core;
+pub mod errors;
+pub mod inspect;
+pub mod progress;
+pub mod streaming;
+pub mod systems;
+
+use crate::assets::*;
+use crate::core::*;
+use bevy::app::PluginGroupBuilder;
+use bevy::asset::processor::*;
+use bevy::prelude::*;
+use bevy::render::settings::WgpuSettings;
+use bevy::render::RenderPlugin;
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+
+pub const LOG_LEVEL: &str =
+    "naga::back::spv::writer=warn,bevy_ecs::world=error,bevy_gltf::loader=error,bevy_asset::server::loaders=error,meshlet_terrain_testing=debug";
+
+#[derive(States, PartialEq, Eq, Debug, Default, Hash, Copy, Clone)]
+/// The overall state enum for this game.
+pub enum GameState {
+    /// Loads the terrain details asset
+    #[default]
+    Startup,
+    /// Shows the terrain load error, with an option to retry
+    LoadFailed,
+    InGame,
+}
+
+/// Registers the terrain assets, loaders & processor.
+/// Shared by the game and the headless `terrain-process` tool.
+pub struct TerrainAssetsPlugin;
+
+impl Plugin for TerrainAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        let timings = TerrainProcessTimings::default();
+
+        app.add_plugins(TerrainMaterialPlugin)
+            .insert_resource(timings.clone())
+            .init_asset::<ProcessedTerrainDetails>()
+            .init_asset::<TerrainDetails>()
+            .init_asset::<TerrainChunk>()
+            .register_asset_loader(ProcessedTerrainDetailsAssetLoader { timings })
+            .register_asset_loader(TerrainDetailsAssetLoader)
+            .register_asset_processor::<LoadAndSave<ProcessedTerrainDetailsAssetLoader, ProcessedTerrainSaver>>(
+                LoadAndSave::from(ProcessedTerrainSaver),
+            )
+            .set_default_asset_processor::<LoadAndSave<ProcessedTerrainDetailsAssetLoader, ProcessedTerrainSaver>>(
+                TERRAIN_DETAILS_FILE_EXTENSION,
+            );
+    }
+}
+
+/// The default plugins, configured to run without a window or GPU (e.g. for processing terrain on build machines).
+pub fn headless_plugins(asset_plugin: AssetPlugin) -> PluginGroupBuilder {
+    DefaultPlugins
+        .build()
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .set(RenderPlugin {
+            // No GPU required, as processing only happens on the CPU
+            render_creation: WgpuSettings {
+                backends: None,
+                ..default()
+            }
+            .into(),
+            ..default()
+        })
+        .set(asset_plugin)
+        .disable::<WinitPlugin>()
+}
@@ -1,13 +1,3 @@
-mod assets;
-mod components;
-mod core;
-mod errors;
-mod systems;
-
-use crate::assets::*;
-use crate::core::*;
-use crate::systems::*;
-use bevy::asset::processor::*;
 use bevy::core::TaskPoolThreadAssignmentPolicy;
 use bevy::pbr::experimental::meshlet::MeshletPlugin;
 use bevy::prelude::*;
@@ -17,20 +7,13 @@ use bevy_atmosphere::prelude::*;
 use bevy_mod_wanderlust::*;
 use bevy_rapier3d::prelude::*;
 use bevy_water::WaterPlugin;
+use meshlet_terrain_testing::assets::*;
+use meshlet_terrain_testing::progress::*;
+use meshlet_terrain_testing::streaming::*;
+use meshlet_terrain_testing::systems::*;
+use meshlet_terrain_testing::*;
 
-const LOG_LEVEL: &str =
-    "naga::back::spv::writer=warn,bevy_ecs::world=error,bevy_gltf::loader=error,bevy_asset::server::loaders=error,meshlet_terrain_testing=debug";
-
-#[derive(States, PartialEq, Eq, Debug, Default, Hash, Copy, Clone)]
-/// The overall state enum for this game.
-pub enum GameState {
-    /// Loads the terrain details asset
-    #[default]
-    Startup,
-    InGame,
-}
-
-fn main() {
+fn main() -> AppExit {
     App::new()
         .add_plugins((
             DefaultPlugins
@@ -69,42 +52,84 @@ fn main() {
             // Water
             WaterPlugin,
             // Wanderlust
-            WanderlustPlugin::default()
+            WanderlustPlugin::default(),
+            // Terrain
+            TerrainAssetsPlugin,
         ))
         // Atmosphere
         .insert_resource(AtmosphereModel::default())
         // GameState
         .init_state::<GameState>()
-        // Assets
-        .init_asset::<ProcessedTerrainDetails>()
-        .init_asset::<TerrainDetails>()
-        .register_asset_loader(ProcessedTerrainDetailsAssetLoader)
-        .register_asset_loader(TerrainDetailsAssetLoader)
-        .register_asset_processor::<LoadAndSave<ProcessedTerrainDetailsAssetLoader, ProcessedTerrainSaver>>(
-            LoadAndSave::from(ProcessedTerrainSaver),
-        )
-        .set_default_asset_processor::<LoadAndSave<ProcessedTerrainDetailsAssetLoader, ProcessedTerrainSaver>>(
-            TERRAIN_DETAILS_FILE_EXTENSION,
-        )
+        .enable_state_scoped_entities::<GameState>()
+        .insert_resource(TerrainSelection::from_args_or_env())
+        .insert_resource(TerrainStartupSettings {
+            exit_on_failure: std::env::args().any(|arg| arg == "--exit-on-failure"),
+            show_progress_overlay: !std::env::args().any(|arg| arg == "--no-progress-overlay"),
+        })
+        // Progress
+        .init_resource::<TerrainLoadProgress>()
+        // Streaming
+        .init_resource::<TerrainStreamingSettings>()
         // Systems - OnEnter GameState::Startup
-        .add_systems(OnEnter(GameState::Startup), asset_startup_enter_system)
+        .add_systems(
+            OnEnter(GameState::Startup),
+            (
+                asset_startup_enter_system,
+                reset_load_progress_enter_system,
+                spawn_load_progress_overlay_enter_system.run_if(show_progress_overlay),
+            ),
+        )
         // Systems - Update GameState::Startup
         .add_systems(
             Update,
-            (finalise_startup_system, startup_load_complete_system)
+            (
+                finalise_startup_system,
+                terrain_load_progress_system,
+                update_load_progress_overlay_system.run_if(show_progress_overlay),
+                startup_load_complete_system,
+            )
+                .chain()
                 .distributive_run_if(in_state(GameState::Startup)),
         )
+        // Systems - OnEnter GameState::LoadFailed
+        .add_systems(OnEnter(GameState::LoadFailed), load_failed_enter_system)
+        // Systems - Update GameState::LoadFailed
+        .add_systems(
+            Update,
+            load_failed_input_system.run_if(in_state(GameState::LoadFailed)),
+        )
         // Systems - OnEnter GameState::InGame
         .add_systems(
             OnEnter(GameState::InGame),
-            (spawn_player_enter_system, spawn_sun_enter_system, hide_mouse_enter_system),
+            (
+                spawn_player_enter_system,
+                spawn_sun_enter_system,
+                hide_mouse_enter_system,
+            ),
         )
         // Systems - Update GameState::InGame
         .add_systems(
             Update,
-            (movement_input_system, toggle_mouse_visibility_system).distributive_run_if(in_state(GameState::InGame)),
+            (
+                movement_input_system,
+                toggle_mouse_visibility_system,
+                terrain_selection_changed_system,
+            )
+                .distributive_run_if(in_state(GameState::InGame)),
+        )
+        .add_systems(
+            Update,
+            terrain_chunk_streaming_system.run_if(
+                in_state(GameState::InGame).and_then(resource_exists::<TerrainChunkStreamer>),
+            ),
         )
+        // Systems - OnExit GameState::InGame
+        .add_systems(OnExit(GameState::InGame), remove_chunk_streamer_exit_system)
         // Systems - Update
         .add_systems(Update, mouse_look)
-        .run();
+        .run()
+}
+
+fn show_progress_overlay(settings: Res<TerrainStartupSettings>) -> bool {
+    settings.show_progress_overlay
 }
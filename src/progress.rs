@@ -0,0 +1,223 @@
+use crate::assets::*;
+use crate::core::*;
+use crate::GameState;
+use bevy::asset::processor::{AssetProcessor, ProcessorState};
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future};
+use std::fmt;
+
+//****************************************************************************
+// RESOURCES
+//****************************************************************************
+
+/// How far through loading the terrain the Startup GameState is.
+/// Updated every frame whilst in the Startup GameState.
+#[derive(Resource, Default, Clone, Debug)]
+pub struct TerrainLoadProgress {
+    pub stage: TerrainLoadStage,
+    /// Fraction (`0.0..=1.0`) of the terrain's assets which have loaded (including their dependencies).
+    pub fraction: f32,
+    /// Whether the asset processor has finished (it's only polled until then).
+    processing_finished: bool,
+}
+
+#[derive(Default, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum TerrainLoadStage {
+    /// The asset processor is generating the meshlets & colliders (only happens when the terrain changes).
+    #[default]
+    Processing,
+    LoadingGltf,
+    /// The gLTFs have loaded, but terrain material textures are still loading.
+    /// (Meshlets are loaded with the terrain file itself, so are never waited on.)
+    LoadingMaterials,
+    /// Everything has loaded, and the terrain & its colliders are being spawned.
+    /// Shown for the frame they're spawned in, before the GameState changes to InGame.
+    SpawningColliders,
+    Failed,
+}
+
+impl fmt::Display for TerrainLoadStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Processing => "Processing terrain",
+            Self::LoadingGltf => "Loading terrain gLTF",
+            Self::LoadingMaterials => "Loading terrain materials",
+            Self::SpawningColliders => "Spawning terrain colliders",
+            Self::Failed => "Failed to load terrain",
+        })
+    }
+}
+
+//****************************************************************************
+// COMPONENTS
+//****************************************************************************
+
+/// Marker component for the progress overlay's text.
+#[derive(Component)]
+pub struct TerrainLoadProgressText;
+
+/// Marker component for the filled part of the progress overlay's bar.
+#[derive(Component)]
+pub struct TerrainLoadProgressBar;
+
+//****************************************************************************
+// ENTER SYSTEMS - GAMESTATE:STARTUP
+//****************************************************************************
+
+/// Resets TerrainLoadProgress, as the processor may be processing the newly selected terrain.
+/// Runs upon entering the Startup GameState.
+pub fn reset_load_progress_enter_system(mut progress: ResMut<TerrainLoadProgress>) {
+    *progress = TerrainLoadProgress::default();
+}
+
+/// Spawns the progress overlay (removed when leaving the Startup GameState).
+/// Runs upon entering the Startup GameState.
+/// Has run condition: TerrainStartupSettings::show_progress_overlay.
+pub fn spawn_load_progress_overlay_enter_system(mut commands: Commands) {
+    commands.spawn((Camera2dBundle::default(), StateScoped(GameState::Startup)));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(12.0),
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(GameState::Startup),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    TerrainLoadStage::default().to_string(),
+                    TextStyle {
+                        font_size: 24.0,
+                        ..default()
+                    },
+                ),
+                TerrainLoadProgressText,
+            ));
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(400.0),
+                        height: Val::Px(12.0),
+                        ..default()
+                    },
+                    background_color: Color::srgb(0.15, 0.15, 0.15).into(),
+                    ..default()
+                })
+                .with_children(|bar_parent| {
+                    bar_parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..default()
+                            },
+                            background_color: Color::srgb(0.3, 0.7, 0.4).into(),
+                            ..default()
+                        },
+                        TerrainLoadProgressBar,
+                    ));
+                });
+        });
+}
+
+//****************************************************************************
+// UPDATE SYSTEMS - GAMESTATE:STARTUP
+//****************************************************************************
+
+/// Updates TerrainLoadProgress from the asset processor & asset server.
+/// Runs in the Startup GameState (after finalise_startup_system).
+pub fn terrain_load_progress_system(
+    // Resources
+    mut progress: ResMut<TerrainLoadProgress>,
+    manager: Res<TerrainStartupManager>,
+    asset_server: Res<AssetServer>,
+    processor: Option<Res<AssetProcessor>>,
+    // Assets
+    terrain_details_assets: Res<Assets<TerrainDetails>>,
+) {
+    let terrain_details = terrain_details_assets.get(&manager.terrain_detail_handle);
+
+    let (stage, fraction) = match (manager.state, terrain_details) {
+        (AssetLoadState::Failed, _) => (TerrainLoadStage::Failed, progress.fraction),
+        (AssetLoadState::Loaded, _) => (TerrainLoadStage::SpawningColliders, 1.0),
+        (AssetLoadState::Loading, Some(terrain_details)) => {
+            let asset_ids = terrain_details.asset_ids();
+            let loaded = asset_ids
+                .iter()
+                .filter(|id| asset_server.is_loaded_with_dependencies(**id))
+                .count();
+
+            let gltf_loaded = terrain_details
+                .gltf_handles
+                .iter()
+                .all(|gltf_handle| asset_server.is_loaded_with_dependencies(gltf_handle.id()));
+
+            let stage = if gltf_loaded {
+                TerrainLoadStage::LoadingMaterials
+            } else {
+                TerrainLoadStage::LoadingGltf
+            };
+
+            (stage, loaded as f32 / asset_ids.len().max(1) as f32)
+        }
+        (AssetLoadState::Loading, None) => {
+            // Processed terrain can't be read until the processor has finished with it
+            // The state is polled without waiting (in case the processor holds its lock), and cached once finished
+            if !progress.processing_finished {
+                progress.processing_finished = processor.map_or(true, |processor| {
+                    block_on(future::poll_once(processor.get_state()))
+                        == Some(ProcessorState::Finished)
+                });
+            }
+
+            let stage = if !progress.processing_finished {
+                TerrainLoadStage::Processing
+            } else {
+                TerrainLoadStage::LoadingGltf
+            };
+
+            (stage, 0.0)
+        }
+    };
+
+    if progress.stage != stage {
+        debug!("Terrain load stage: {}", stage);
+    }
+
+    progress.stage = stage;
+    progress.fraction = fraction;
+}
+
+/// Shows TerrainLoadProgress in the overlay.
+/// Runs in the Startup GameState.
+pub fn update_load_progress_overlay_system(
+    // Resources
+    progress: Res<TerrainLoadProgress>,
+    // Queries
+    mut text_query: Query<&mut Text, With<TerrainLoadProgressText>>,
+    mut bar_query: Query<&mut Style, With<TerrainLoadProgressBar>>,
+) {
+    if !progress.is_changed() {
+        return;
+    }
+
+    for mut text in &mut text_query {
+        text.sections[0].value =
+            format!("{0}... {1:.0}%", progress.stage, progress.fraction * 100.0);
+    }
+
+    for mut style in &mut bar_query {
+        style.width = Val::Percent(progress.fraction * 100.0);
+    }
+}
@@ -0,0 +1,296 @@
+use crate::assets::*;
+use crate::components::*;
+use crate::core::*;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+//****************************************************************************
+// RESOURCES
+//****************************************************************************
+
+/// Controls which terrain chunks are spawned around the player.
+/// Chunks are spawned within `load_radius` and despawned beyond `unload_radius` (which should be larger, to avoid
+/// chunks repeatedly spawning & despawning at the boundary).
+#[derive(Resource, Clone, Debug)]
+pub struct TerrainStreamingSettings {
+    pub load_radius: f32,
+    pub unload_radius: f32,
+    /// Maximum number of chunks spawned per frame.
+    pub max_spawns_per_frame: usize,
+    /// Maximum number of chunks despawned per frame.
+    pub max_despawns_per_frame: usize,
+}
+
+impl Default for TerrainStreamingSettings {
+    fn default() -> Self {
+        Self {
+            load_radius: 256.0,
+            unload_radius: 320.0,
+            max_spawns_per_frame: 2,
+            max_despawns_per_frame: 4,
+        }
+    }
+}
+
+/// Tracks the spawned chunks of a chunked terrain.
+/// Inserted once the terrain has loaded (if it has chunks).
+#[derive(Resource)]
+pub struct TerrainChunkStreamer {
+    /// The entity chunks are spawned underneath.
+    pub terrain_entity: Entity,
+    pub spawned_chunks: HashMap<IVec2, Entity>,
+}
+
+//****************************************************************************
+// UPDATE SYSTEMS - GAMESTATE:INGAME
+//****************************************************************************
+
+/// Spawns chunks near the player, and despawns those that are far away (closest/furthest first, within the per-frame budgets).
+/// Runs in the InGame GameState.
+/// Has run condition: resource_exists::<TerrainChunkStreamer>.
+pub fn terrain_chunk_streaming_system(
+    mut commands: Commands,
+    // Resources
+    settings: Res<TerrainStreamingSettings>,
+    manager: Res<TerrainStartupManager>,
+    mut streamer: ResMut<TerrainChunkStreamer>,
+    // Assets
+    terrain_details_assets: Res<Assets<TerrainDetails>>,
+    terrain_chunk_assets: Res<Assets<TerrainChunk>>,
+    // Queries
+    player_query: Query<&GlobalTransform, With<Player>>,
+) {
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
+
+    let Some(terrain_details) = terrain_details_assets.get(&manager.terrain_detail_handle) else {
+        return;
+    };
+
+    let (chunks_to_spawn, chunks_to_despawn) = select_chunks_to_stream(
+        terrain_details
+            .chunks
+            .iter()
+            .filter_map(|chunk_handle| terrain_chunk_assets.get(chunk_handle)),
+        &streamer.spawned_chunks,
+        player_transform.translation().xz(),
+        &settings,
+    );
+
+    for chunk in chunks_to_spawn {
+        let mut chunk_entity = Entity::PLACEHOLDER;
+
+        commands
+            .entity(streamer.terrain_entity)
+            .with_children(|parent| chunk_entity = chunk.spawn(parent));
+
+        streamer.spawned_chunks.insert(chunk.coord, chunk_entity);
+        debug!("Spawned terrain chunk {}", chunk.coord);
+    }
+
+    for chunk_entity in chunks_to_despawn {
+        commands.entity(chunk_entity).despawn_recursive();
+        streamer
+            .spawned_chunks
+            .retain(|_, spawned_entity| *spawned_entity != chunk_entity);
+    }
+}
+
+//****************************************************************************
+// EXIT SYSTEMS - GAMESTATE:INGAME
+//****************************************************************************
+
+/// Stops streaming chunks (the terrain entity is despawned when leaving InGame).
+/// Runs upon exiting the InGame GameState.
+pub fn remove_chunk_streamer_exit_system(mut commands: Commands) {
+    commands.remove_resource::<TerrainChunkStreamer>();
+}
+
+//****************************************************************************
+// UTILITY
+//****************************************************************************
+
+/// Returns the chunks to spawn (closest first) and the spawned chunk entities to despawn (furthest first),
+/// limited to the per-frame budgets.
+/// Chunks between the load & unload radii are left as they are.
+fn select_chunks_to_stream<'a>(
+    chunks: impl Iterator<Item = &'a TerrainChunk>,
+    spawned_chunks: &HashMap<IVec2, Entity>,
+    player_position: Vec2,
+    settings: &TerrainStreamingSettings,
+) -> (Vec<&'a TerrainChunk>, Vec<Entity>) {
+    let unload_radius = settings.unload_radius.max(settings.load_radius);
+
+    let mut chunks_to_spawn = Vec::new();
+    let mut chunks_to_despawn = Vec::new();
+
+    for chunk in chunks {
+        let distance = chunk_distance(chunk, player_position);
+
+        match spawned_chunks.get(&chunk.coord) {
+            None if distance <= settings.load_radius => chunks_to_spawn.push((distance, chunk)),
+            Some(entity) if distance > unload_radius => chunks_to_despawn.push((distance, *entity)),
+            _ => {}
+        }
+    }
+
+    chunks_to_spawn.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    chunks_to_despawn.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    (
+        chunks_to_spawn
+            .into_iter()
+            .take(settings.max_spawns_per_frame)
+            .map(|(_, chunk)| chunk)
+            .collect(),
+        chunks_to_despawn
+            .into_iter()
+            .take(settings.max_despawns_per_frame)
+            .map(|(_, entity)| entity)
+            .collect(),
+    )
+}
+
+/// Distance on the XZ plane from the position to the chunk's bounds (zero if inside).
+fn chunk_distance(chunk: &TerrainChunk, position: Vec2) -> f32 {
+    position.distance(position.clamp(chunk.min.xz(), chunk.max.xz()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHUNK_SIZE: f32 = 10.0;
+
+    fn test_chunk(x: i32, z: i32) -> TerrainChunk {
+        let coord = IVec2::new(x, z);
+        let min = (coord.as_vec2() * CHUNK_SIZE).extend(0.0).xzy();
+        let max = ((coord + 1).as_vec2() * CHUNK_SIZE).extend(5.0).xzy();
+
+        TerrainChunk {
+            coord,
+            min,
+            max,
+            meshlet_node: MeshletNode {
+                meshlets: Vec::new(),
+                transform: Transform::IDENTITY,
+                children: Vec::new(),
+            },
+            collider_node: TerrainColliderNode {
+                colliders: Vec::new(),
+                collider_shape: TerrainColliderShape::TriMesh,
+                physics_material: None,
+                collision_group: None,
+                transform: Transform::IDENTITY,
+                children: Vec::new(),
+            },
+        }
+    }
+
+    fn test_settings() -> TerrainStreamingSettings {
+        TerrainStreamingSettings {
+            load_radius: 15.0,
+            unload_radius: 25.0,
+            max_spawns_per_frame: 10,
+            max_despawns_per_frame: 10,
+        }
+    }
+
+    fn coords(chunks: &[&TerrainChunk]) -> Vec<IVec2> {
+        chunks.iter().map(|chunk| chunk.coord).collect()
+    }
+
+    #[test]
+    fn measures_distance_to_chunk_bounds() {
+        let chunk = test_chunk(1, 0);
+
+        assert_eq!(chunk_distance(&chunk, Vec2::new(15.0, 5.0)), 0.0);
+        assert_eq!(chunk_distance(&chunk, Vec2::new(5.0, 5.0)), 5.0);
+        assert_eq!(chunk_distance(&chunk, Vec2::new(23.0, 14.0)), 5.0);
+    }
+
+    #[test]
+    fn spawns_chunks_within_load_radius() {
+        let chunks = [test_chunk(0, 0), test_chunk(2, 0), test_chunk(3, 0)];
+
+        let (to_spawn, to_despawn) = select_chunks_to_stream(
+            chunks.iter(),
+            &HashMap::new(),
+            Vec2::new(5.0, 5.0),
+            &test_settings(),
+        );
+
+        // (3, 0) is 25 away, beyond the load radius
+        assert_eq!(coords(&to_spawn), vec![IVec2::new(0, 0), IVec2::new(2, 0)]);
+        assert!(to_despawn.is_empty());
+    }
+
+    #[test]
+    fn keeps_chunks_between_load_and_unload_radii() {
+        // (2, 0) is 15 away (beyond the load radius) and unspawned, whilst (3, 0) is 25 away (within the unload radius)
+        // and spawned, so both are left as they are
+        let chunks = [test_chunk(2, 0), test_chunk(3, 0)];
+        let player_position = Vec2::new(5.0, 5.0);
+        let spawned_entity = Entity::from_raw(1);
+        let spawned_chunks = HashMap::from_iter([(IVec2::new(3, 0), spawned_entity)]);
+
+        let (to_spawn, to_despawn) = select_chunks_to_stream(
+            chunks.iter(),
+            &spawned_chunks,
+            player_position,
+            &TerrainStreamingSettings {
+                load_radius: 14.0,
+                ..test_settings()
+            },
+        );
+
+        assert!(to_spawn.is_empty());
+        assert!(to_despawn.is_empty());
+
+        // Moving away leaves (3, 0) beyond the unload radius
+        let (_, to_despawn) = select_chunks_to_stream(
+            chunks.iter(),
+            &spawned_chunks,
+            player_position - Vec2::X,
+            &test_settings(),
+        );
+
+        assert_eq!(to_despawn, vec![spawned_entity]);
+    }
+
+    #[test]
+    fn limits_spawns_and_despawns_per_update() {
+        let near_chunks: Vec<TerrainChunk> = (0..4).map(|z| test_chunk(0, z - 2)).collect();
+        let far_chunks: Vec<TerrainChunk> = (0..5).map(|z| test_chunk(10, z)).collect();
+        let spawned_chunks: HashMap<IVec2, Entity> = far_chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| (chunk.coord, Entity::from_raw(index as u32)))
+            .collect();
+
+        let settings = TerrainStreamingSettings {
+            max_spawns_per_frame: 2,
+            max_despawns_per_frame: 3,
+            ..test_settings()
+        };
+
+        let (to_spawn, to_despawn) = select_chunks_to_stream(
+            near_chunks.iter().chain(&far_chunks),
+            &spawned_chunks,
+            Vec2::new(5.0, 5.0),
+            &settings,
+        );
+
+        // The closest chunks are spawned, and the furthest despawned first
+        assert_eq!(coords(&to_spawn), vec![IVec2::new(0, 0), IVec2::new(0, -1)]);
+        assert_eq!(
+            to_despawn,
+            vec![
+                Entity::from_raw(4),
+                Entity::from_raw(3),
+                Entity::from_raw(2)
+            ]
+        );
+    }
+}
@@ -19,16 +19,111 @@ const CAMERA_VERTICAL_OFFSET: f32 = 1.5;
 // UPDATE SYSTEMS - GAMESTATE:STARTUP
 //****************************************************************************
 
-/// Transitions the GameState to InGame.
+/// Transitions the GameState to InGame (or LoadFailed).
 /// Runs in the Startup GameState.
 pub fn startup_load_complete_system(
     // Resources
     terrain_manager: Res<TerrainStartupManager>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    if terrain_manager.state == AssetLoadState::Loaded {
-        info!("All startup assets loaded. Progressing to InGame");
-        next_state.set(GameState::InGame);
+    match terrain_manager.state {
+        AssetLoadState::Loaded => {
+            info!("All startup assets loaded. Progressing to InGame");
+            next_state.set(GameState::InGame);
+        }
+        AssetLoadState::Failed => next_state.set(GameState::LoadFailed),
+        AssetLoadState::Loading => {}
+    }
+}
+
+//****************************************************************************
+// ENTER SYSTEMS - GAMESTATE:LOADFAILED
+//****************************************************************************
+
+/// Shows the load error with a retry prompt, or exits if TerrainStartupSettings::exit_on_failure is set.
+/// Runs upon entering the LoadFailed GameState.
+pub fn load_failed_enter_system(
+    mut commands: Commands,
+    // Resources
+    settings: Res<TerrainStartupSettings>,
+    terrain_manager: Res<TerrainStartupManager>,
+    // Events
+    mut exit_events: EventWriter<AppExit>,
+) {
+    let error = terrain_manager.error.as_deref().unwrap_or("Unknown error");
+
+    // The error has already been logged by `finalise_startup_system`
+    if settings.exit_on_failure {
+        exit_events.send(AppExit::error());
+        return;
+    }
+
+    commands.spawn((
+        Camera2dBundle::default(),
+        StateScoped(GameState::LoadFailed),
+    ));
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(16.0),
+                    padding: UiRect::all(Val::Px(32.0)),
+                    ..default()
+                },
+                ..default()
+            },
+            StateScoped(GameState::LoadFailed),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Failed to load terrain",
+                TextStyle {
+                    font_size: 40.0,
+                    color: Color::srgb(1.0, 0.3, 0.3),
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                error,
+                TextStyle {
+                    font_size: 20.0,
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                "Press R to retry, or Escape to quit",
+                TextStyle {
+                    font_size: 20.0,
+                    ..default()
+                },
+            ));
+        });
+}
+
+//****************************************************************************
+// UPDATE SYSTEMS - GAMESTATE:LOADFAILED
+//****************************************************************************
+
+/// Returns to the Startup GameState (which reloads the terrain) when R is pressed, or exits on Escape.
+/// Runs in the LoadFailed GameState.
+pub fn load_failed_input_system(
+    // Resources
+    input: Res<ButtonInput<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    // Events
+    mut exit_events: EventWriter<AppExit>,
+) {
+    if input.just_pressed(KeyCode::KeyR) {
+        info!("Retrying terrain load");
+        next_state.set(GameState::Startup);
+    } else if input.just_pressed(KeyCode::Escape) {
+        exit_events.send(AppExit::Success);
     }
 }
 
@@ -41,6 +136,7 @@ pub fn spawn_player_enter_system(mut commands: Commands) {
         .spawn((
             Name::from("Player"),
             Player,
+            StateScoped(GameState::InGame),
             ControllerBundle {
                 controller: Controller {
                     movement: Movement {
@@ -86,6 +182,7 @@ pub fn spawn_player_enter_system(mut commands: Commands) {
 pub fn spawn_sun_enter_system(mut commands: Commands) {
     commands.spawn((
         Name::from("Sun"),
+        StateScoped(GameState::InGame),
         DirectionalLightBundle {
             directional_light: DirectionalLight {
                 color: Color::srgba_u8(250, 255, 230, 0),
@@ -145,6 +242,21 @@ pub fn movement_input_system(
     player_input.jumping = input.pressed(KeyCode::Space);
 }
 
+/// Returns to the Startup GameState to load a different terrain when TerrainSelection is changed.
+/// Leaving InGame despawns the current terrain, player & sun.
+/// Runs in the InGame GameState.
+pub fn terrain_selection_changed_system(
+    // Resources
+    selection: Res<TerrainSelection>,
+    terrain_manager: Res<TerrainStartupManager>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if selection.path != terrain_manager.terrain_path {
+        info!("Switching terrain to {}", selection.path);
+        next_state.set(GameState::Startup);
+    }
+}
+
 pub fn toggle_mouse_visibility_system(
     // Resources
     input: Res<ButtonInput<KeyCode>>,
@@ -229,3 +341,32 @@ pub fn set_cursor_visible(window: &mut Window, visible: bool) {
         false => CursorGrabMode::Locked,
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    #[test]
+    fn load_failure_exits_with_error() {
+        let mut world = World::new();
+        world.init_resource::<Events<AppExit>>();
+        world.insert_resource(TerrainStartupSettings {
+            exit_on_failure: true,
+            show_progress_overlay: false,
+        });
+        world.insert_resource(TerrainStartupManager {
+            state: AssetLoadState::Failed,
+            terrain_path: "missing.terrain.bin".to_string(),
+            terrain_detail_handle: Handle::default(),
+            error: Some("Path not found".to_string()),
+        });
+
+        world.run_system_once(load_failed_enter_system);
+
+        let exit_events = world.resource::<Events<AppExit>>();
+        let exits: Vec<&AppExit> = exit_events.get_reader().read(exit_events).collect();
+        assert_eq!(exits.len(), 1);
+        assert!(exits[0].is_error());
+    }
+}
@@ -0,0 +1,484 @@
+//! Processes a small procedural gLTF through a headless app, then checks the reloaded terrain.
+//! Runs without a window or GPU.
+
+use bevy::asset::RecursiveDependencyLoadState;
+use bevy::pbr::experimental::meshlet::{MeshletMesh, MeshletPlugin};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use meshlet_terrain_testing::assets::*;
+use meshlet_terrain_testing::core::*;
+use meshlet_terrain_testing::*;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const LOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Number of vertices along each side of the test grid.
+const GRID_VERTICES: u32 = 9;
+
+/// Writes a gLTF with a scene of `Node0` (mesh 0, materials 1 & 0) -> `Node1` & `Node3` (both mesh 1, material 0).
+/// Both meshes are a grid with a raised centre vertex (so it isn't flat).
+/// `Node4` is a collision node for `Node1`, and `Node2` isn't part of the scene, so shouldn't be processed.
+/// `Node0`'s extras override its collider shape to a box, mesh 1's extras give it a physics material,
+/// and `Node3`'s extras put it in a collision group.
+/// The `Rock` material is replaced by a terrain material.
+/// A second gLTF (`Prop` -> `PropInstance`, both mesh 0, material 0), sharing the same buffer, is placed alongside it.
+fn write_test_gltf(folder: &Path) {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+
+    for z in 0..GRID_VERTICES {
+        for x in 0..GRID_VERTICES {
+            let uv = [x as f32, z as f32].map(|value| value / (GRID_VERTICES - 1) as f32);
+            let height = if x == GRID_VERTICES / 2 && z == GRID_VERTICES / 2 {
+                1.0
+            } else {
+                0.0
+            };
+            positions.extend([x as f32, height, z as f32]);
+            normals.extend([0.0, 1.0, 0.0]);
+            uvs.extend(uv);
+        }
+    }
+
+    let mut indices = Vec::new();
+
+    for z in 0..GRID_VERTICES - 1 {
+        for x in 0..GRID_VERTICES - 1 {
+            let index = z * GRID_VERTICES + x;
+            let front = index + GRID_VERTICES;
+            indices.extend([index, front, index + 1, index + 1, front, front + 1]);
+        }
+    }
+
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut buffer_views = Vec::new();
+
+    for (bytes, target) in [
+        (f32_bytes(&positions), 34962),
+        (f32_bytes(&normals), 34962),
+        (f32_bytes(&uvs), 34962),
+        (
+            indices
+                .iter()
+                .flat_map(|index| index.to_le_bytes())
+                .collect(),
+            34963,
+        ),
+    ] {
+        buffer_views.push(format!(
+            r#"{{"buffer": 0, "byteOffset": {0}, "byteLength": {1}, "target": {2}}}"#,
+            buffer.len(),
+            bytes.len(),
+            target
+        ));
+        buffer.extend(bytes);
+    }
+
+    let vertex_count = GRID_VERTICES * GRID_VERTICES;
+    let max = (GRID_VERTICES - 1) as f32;
+    let primitive = |material: usize| {
+        format!(
+            r#"{{"attributes": {{"POSITION": 0, "NORMAL": 1, "TEXCOORD_0": 2}}, "indices": 3, "material": {}}}"#,
+            material
+        )
+    };
+
+    let buffers = format!(
+        r#""buffers": [{{"uri": "test_terrain.bin", "byteLength": {buffer_length}}}],
+    "bufferViews": [{buffer_views}],
+    "accessors": [
+        {{"bufferView": 0, "componentType": 5126, "count": {vertex_count}, "type": "VEC3", "min": [0.0, 0.0, 0.0], "max": [{max}, 1.0, {max}]}},
+        {{"bufferView": 1, "componentType": 5126, "count": {vertex_count}, "type": "VEC3"}},
+        {{"bufferView": 2, "componentType": 5126, "count": {vertex_count}, "type": "VEC2"}},
+        {{"bufferView": 3, "componentType": 5125, "count": {index_count}, "type": "SCALAR"}}
+    ]"#,
+        buffer_length = buffer.len(),
+        buffer_views = buffer_views.join(", "),
+        index_count = indices.len(),
+    );
+
+    let gltf = format!(
+        r#"{{
+    "asset": {{"version": "2.0"}},
+    "scene": 0,
+    "scenes": [{{"nodes": [0]}}],
+    "nodes": [
+        {{"name": "Root", "mesh": 0, "children": [1, 3, 4], "extras": {{"collider": "box"}}}},
+        {{"name": "Child", "mesh": 1, "translation": [0.0, 2.0, 0.0]}},
+        {{"name": "Unused", "mesh": 1}},
+        {{"name": "Instance", "mesh": 1, "translation": [4.0, 0.0, 0.0], "extras": {{"collision_group": 2}}}},
+        {{"name": "UCX_Child_01", "mesh": 1, "translation": [0.0, 2.0, 0.0]}}
+    ],
+    "meshes": [
+        {{"primitives": [{primitive_1}, {primitive_0}]}},
+        {{"primitives": [{primitive_0}], "extras": {{"physics_material": "Ice"}}}}
+    ],
+    "materials": [{{"name": "Grass"}}, {{"name": "Rock"}}],
+    {buffers}
+}}"#,
+        primitive_0 = primitive(0),
+        primitive_1 = primitive(1),
+    );
+
+    let prop_gltf = format!(
+        r#"{{
+    "asset": {{"version": "2.0"}},
+    "scene": 0,
+    "scenes": [{{"nodes": [0]}}],
+    "nodes": [
+        {{"name": "Prop", "mesh": 0, "children": [1], "translation": [0.0, 1.0, 0.0]}},
+        {{"name": "PropInstance", "mesh": 0, "translation": [2.0, 0.0, 0.0]}}
+    ],
+    "meshes": [{{"primitives": [{primitive_0}]}}],
+    "materials": [{{"name": "Stone"}}],
+    {buffers}
+}}"#,
+        primitive_0 = primitive(0),
+    );
+
+    std::fs::write(folder.join("test_terrain.gltf"), gltf).unwrap();
+    std::fs::write(folder.join("test_prop.gltf"), prop_gltf).unwrap();
+    std::fs::write(folder.join("test_terrain.bin"), buffer).unwrap();
+    std::fs::write(folder.join("layer.png"), LAYER_PNG).unwrap();
+    std::fs::write(
+        folder.join("test.terrain.bin"),
+        r#"(
+    gltf_path: "test_terrain.gltf",
+    gltfs: [(path: "test_prop.gltf", placement: (translation: (10.0, 0.0, 0.0), rotation: (0.0, 90.0, 0.0)))],
+    terrain_materials: {
+        "Rock": (
+            layers: [(texture: "layer.png", tiling: 4.0), (texture: "layer.png")],
+            height_blend: 0.5,
+        ),
+    },
+    physics_materials: {"Ice": (friction: 0.05)},
+)"#,
+    )
+    .unwrap();
+}
+
+/// A 2x2 grey PNG, used as the texture of each terrain material layer.
+const LAYER_PNG: [u8; 74] = [
+    0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44, 0x52,
+    0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x08, 0x06, 0x00, 0x00, 0x00, 0x72, 0xb6, 0x0d,
+    0x24, 0x00, 0x00, 0x00, 0x11, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x68, 0x68, 0x68, 0xf8,
+    0x0f, 0xc2, 0x0c, 0x30, 0x06, 0x00, 0x56, 0xf4, 0x09, 0xfd, 0x36, 0xa4, 0x76, 0x32, 0x00, 0x00,
+    0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+];
+
+fn f32_bytes(values: &[f32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+#[test]
+fn processed_terrain_round_trip() {
+    let root = std::env::temp_dir().join(format!("terrain_round_trip_{}", std::process::id()));
+    let assets_folder = root.join("assets");
+    let imported_assets_folder = root.join("imported_assets");
+
+    let _ = std::fs::remove_dir_all(&root);
+    std::fs::create_dir_all(&assets_folder).unwrap();
+    write_test_gltf(&assets_folder);
+
+    let mut app = App::new();
+    app.add_plugins((
+        headless_plugins(AssetPlugin {
+            file_path: assets_folder.to_string_lossy().to_string(),
+            processed_file_path: imported_assets_folder.to_string_lossy().to_string(),
+            mode: AssetMode::Processed,
+            ..default()
+        })
+        .disable::<bevy::log::LogPlugin>(),
+        MeshletPlugin,
+        TerrainAssetsPlugin,
+    ));
+    app.finish();
+    app.cleanup();
+
+    // Loading in processed mode waits for the terrain to be processed & saved, then reloads it
+    let handle: Handle<TerrainDetails> = app
+        .world()
+        .resource::<AssetServer>()
+        .load("test.terrain.bin");
+
+    let start = Instant::now();
+
+    loop {
+        app.update();
+
+        match app
+            .world()
+            .resource::<AssetServer>()
+            .recursive_dependency_load_state(&handle)
+        {
+            RecursiveDependencyLoadState::Loaded => break,
+            RecursiveDependencyLoadState::Failed => panic!("Failed to load processed terrain"),
+            _ => {}
+        }
+
+        assert!(start.elapsed() < LOAD_TIMEOUT, "Timed out loading terrain");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    // The processed file
+    let bytes = std::fs::read(imported_assets_folder.join("Default/test.terrain.bin")).unwrap();
+    let (header, processed) = decode_processed_terrain(&bytes).unwrap();
+
+    assert_eq!(header, TerrainFileHeader::current());
+    assert_eq!(
+        processed.gltf_paths,
+        vec!["test_terrain.gltf", "test_prop.gltf"]
+    );
+    // The named `Rock` material is replaced by the terrain material
+    match processed.materials.as_slice() {
+        [SerialisedMaterial::Gltf {
+            source: 0,
+            material: 0,
+        }, SerialisedMaterial::Terrain(terrain_material), SerialisedMaterial::Gltf {
+            source: 1,
+            material: 0,
+        }] => {
+            assert_eq!(terrain_material.layers.len(), 2);
+            assert_eq!(terrain_material.height_blend, 0.5);
+        }
+        _ => panic!("Unexpected processed materials"),
+    }
+    assert_eq!(processed.meshlet_nodes.len(), 2);
+    // Node1 & Node3 share the meshlet mesh of mesh 1, and both prop nodes their meshlet mesh & collider shape
+    assert_eq!(processed.meshlet_meshes.len(), 4);
+    assert_eq!(processed.collider_shapes.len(), 5);
+    assert_eq!(processed.colliders.len(), 2);
+
+    // The second gLTF's nodes are prefixed, its materials offset, and it's placed in the terrain
+    let placement = Transform::from_xyz(10.0, 0.0, 0.0)
+        .with_rotation(Quat::from_rotation_y(90.0_f32.to_radians()));
+    let prop_transform = placement * Transform::from_xyz(0.0, 1.0, 0.0);
+    let prop_node = &processed.meshlet_nodes[1];
+    assert_eq!(prop_node.path, "Gltf1/Node0");
+    assert_eq!(prop_node.meshes.len(), 1);
+    assert_eq!(prop_node.meshes[0].material_index, 2);
+    assert_eq!(
+        processed.meshlet_meshes[prop_node.meshes[0].mesh_index].label,
+        "Meshlet/Gltf1/Mesh0/Primitive0"
+    );
+    assert!(prop_node
+        .transform
+        .translation
+        .abs_diff_eq(prop_transform.translation, 1e-5));
+    assert!(prop_node
+        .transform
+        .rotation
+        .abs_diff_eq(prop_transform.rotation, 1e-5));
+    assert_eq!(prop_node.children[0].path, "Gltf1/Node0/Node1");
+    assert_eq!(
+        prop_node.children[0].meshes[0].mesh_index,
+        prop_node.meshes[0].mesh_index
+    );
+    let prop_collider_node = &processed.colliders[1];
+    assert_eq!(prop_collider_node.colliders.len(), 1);
+    assert!(prop_collider_node
+        .transform
+        .translation
+        .abs_diff_eq(prop_transform.translation, 1e-5));
+    assert_eq!(
+        prop_collider_node.children[0].colliders[0].shape_index,
+        prop_collider_node.colliders[0].shape_index
+    );
+
+    let root_node = &processed.meshlet_nodes[0];
+    assert_eq!(root_node.path, "Node0");
+    assert_eq!(
+        root_node
+            .meshes
+            .iter()
+            .map(|mesh| (mesh.primitive_index, mesh.material_index))
+            .collect::<Vec<_>>(),
+        vec![(0, 1), (1, 0)]
+    );
+    assert_eq!(root_node.children.len(), 3);
+    assert_eq!(root_node.children[0].path, "Node0/Node1");
+    assert_eq!(root_node.children[0].meshes[0].material_index, 0);
+    // Meshlet meshes are labelled by their source rather than the order they're processed in
+    assert_eq!(
+        processed.meshlet_meshes[root_node.children[0].meshes[0].mesh_index].label,
+        "Meshlet/Gltf0/Mesh1/Primitive0"
+    );
+    assert_eq!(root_node.children[1].path, "Node0/Node3");
+    // Every meshlet mesh gets its own label, so sub-assets from different sources/meshes never collide
+    let meshlet_labels: HashSet<&str> = processed
+        .meshlet_meshes
+        .iter()
+        .map(|mesh| mesh.label.as_str())
+        .collect();
+    assert_eq!(meshlet_labels.len(), processed.meshlet_meshes.len());
+    assert_eq!(
+        root_node.children[0].meshes[0].mesh_index,
+        root_node.children[1].meshes[0].mesh_index
+    );
+    // Collision nodes are never turned into meshlets
+    assert_eq!(root_node.children[2].path, "Node0/Node4");
+    assert!(root_node.children[2].meshes.is_empty());
+
+    let root_collider_node = &processed.colliders[0];
+    assert_eq!(root_collider_node.colliders.len(), 2);
+    assert_eq!(
+        root_collider_node.collider_shape,
+        TerrainColliderShape::Cuboid
+    );
+    assert!(
+        processed.collider_shapes[root_collider_node.colliders[0].shape_index]
+            .as_cuboid()
+            .is_some()
+    );
+    assert_eq!(
+        root_collider_node.children[1].collider_shape,
+        TerrainColliderShape::TriMesh
+    );
+    // Node extras are combined with those of their mesh
+    assert_eq!(root_collider_node.physics_material, None);
+    assert_eq!(root_collider_node.children[1].collision_group, Some(2));
+    assert_eq!(root_collider_node.children[2].collision_group, None);
+    assert!(root_collider_node.children[1..]
+        .iter()
+        .all(|child| child.physics_material
+            == Some(TerrainPhysicsMaterial {
+                friction: 0.05,
+                restitution: 0.0
+            })));
+    assert_eq!(root_collider_node.children.len(), 3);
+    // The `UCX_` collision node is a convex hull by default, so doesn't share Node3's trimesh
+    assert_eq!(
+        root_collider_node.children[2].collider_shape,
+        TerrainColliderShape::ConvexHull
+    );
+    assert!(
+        processed.collider_shapes[root_collider_node.children[2].colliders[0].shape_index]
+            .as_convex_polyhedron()
+            .is_some()
+    );
+    assert_ne!(
+        root_collider_node.children[1].colliders[0].shape_index,
+        root_collider_node.children[2].colliders[0].shape_index
+    );
+    // Node1's collider is replaced by its collision node
+    assert!(root_collider_node.children[0].colliders.is_empty());
+    assert_eq!(root_collider_node.children[2].colliders.len(), 1);
+    assert_eq!(
+        root_collider_node.children[0].transform.translation,
+        Vec3::new(0.0, 2.0, 0.0)
+    );
+
+    // The reloaded terrain
+    let world = app.world();
+    let asset_server = world.resource::<AssetServer>();
+    let terrain_details = world
+        .resource::<Assets<TerrainDetails>>()
+        .get(&handle)
+        .unwrap();
+
+    assert_eq!(terrain_details.meshlet_scene.nodes.len(), 2);
+    assert_eq!(terrain_details.colliders.len(), 2);
+    assert!(terrain_details.chunks.is_empty());
+
+    // Instances share the same collider shape
+    let prop_colliders = &terrain_details.colliders[1];
+    assert!(Arc::ptr_eq(
+        &prop_colliders.colliders[0].collider.raw.0,
+        &prop_colliders.children[0].colliders[0].collider.raw.0
+    ));
+
+    let material_paths = |node: &MeshletNode| {
+        node.meshlets
+            .iter()
+            .map(|pair| {
+                asset_server
+                    .get_path(pair.material_handle().id())
+                    .unwrap()
+                    .to_string()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // gLTF materials are referenced from the gLTF, and terrain materials are added to the terrain
+    let root_node = &terrain_details.meshlet_scene.nodes[0];
+    assert_eq!(
+        material_paths(root_node),
+        vec!["test.terrain.bin#Material1", "test_terrain.gltf#Material0"]
+    );
+    assert_eq!(root_node.children.len(), 3);
+    assert_eq!(
+        material_paths(&root_node.children[0]),
+        vec!["test_terrain.gltf#Material0"]
+    );
+
+    let TerrainMaterialHandle::Terrain(terrain_material_handle) =
+        root_node.meshlets[0].material_handle()
+    else {
+        panic!("Expected a terrain material");
+    };
+    let terrain_material = world
+        .resource::<Assets<TerrainMaterial>>()
+        .get(terrain_material_handle)
+        .unwrap();
+    assert_eq!(
+        terrain_material.extension.settings.layer_tiling,
+        Vec4::new(4.0, 1.0, 1.0, 1.0)
+    );
+    assert_eq!(terrain_material.extension.settings.height_blend, 0.5);
+    assert_eq!(terrain_material.extension.settings.layer_count, 2);
+    assert!(terrain_material.extension.layer_1.is_some());
+    assert!(terrain_material.extension.layer_2.is_none());
+
+    let prop_node = &terrain_details.meshlet_scene.nodes[1];
+    assert_eq!(material_paths(prop_node), vec!["test_prop.gltf#Material0"]);
+    assert!(prop_node
+        .transform
+        .translation
+        .abs_diff_eq(prop_transform.translation, 1e-5));
+
+    // Instances share the same meshlet mesh handle
+    assert_eq!(
+        root_node.children[0].meshlets[0].meshlet_handle(),
+        root_node.children[1].meshlets[0].meshlet_handle()
+    );
+    assert_eq!(
+        asset_server
+            .get_path(root_node.children[0].meshlets[0].meshlet_handle().id())
+            .unwrap()
+            .label(),
+        Some("Meshlet/Gltf0/Mesh1/Primitive0")
+    );
+
+    // Sibling nodes (and their parent) load their meshlet meshes from the terrain's labelled sub-assets
+    let meshlet_meshes = world.resource::<Assets<MeshletMesh>>();
+    let meshlet_labels = [root_node, &root_node.children[0], &root_node.children[1]].map(|node| {
+        let handle = node.meshlets[0].meshlet_handle();
+        assert!(meshlet_meshes.contains(handle.id()));
+        asset_server
+            .get_path(handle.id())
+            .unwrap()
+            .label()
+            .unwrap()
+            .to_string()
+    });
+    assert_eq!(
+        meshlet_labels,
+        [
+            "Meshlet/Gltf0/Mesh0/Primitive0",
+            "Meshlet/Gltf0/Mesh1/Primitive0",
+            "Meshlet/Gltf0/Mesh1/Primitive0",
+        ]
+    );
+    assert!(root_node
+        .meshlets
+        .iter()
+        .all(|pair| meshlet_meshes.contains(pair.meshlet_handle().id())));
+
+    let _ = std::fs::remove_dir_all(&root);
+}